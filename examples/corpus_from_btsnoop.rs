@@ -0,0 +1,67 @@
+extern crate btmgmt;
+
+use btmgmt::btsnoop::{self, Direction};
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process;
+
+// event codes of frames answering a command
+const COMMAND_COMPLETE_EVENT: u8 = 0x01;
+const COMMAND_STATUS_EVENT: u8 = 0x02;
+
+// Copies the frames received from the kernel in a btsnoop capture, e.g. one written by
+// `btmon -w`, to the fuzz corpus: command responses go to `response`, events to `event`.
+// Each seed is named after the capture and the record number, and gets a line in the
+// corpus' SOURCES naming the capture, the kernel version and the controller it came from.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 5 {
+        eprintln!(
+            "usage: {} <capture> <corpus dir> <kernel version> <controller>",
+            args[0]
+        );
+        process::exit(2);
+    }
+
+    let capture = Path::new(&args[1]);
+    let corpus = Path::new(&args[2]);
+    let name = capture.file_stem().unwrap().to_string_lossy();
+    let records = btsnoop::read_file(capture).expect("error reading capture");
+    fs::create_dir_all(corpus).unwrap();
+    let mut sources = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(corpus.join("SOURCES"))
+        .expect("error opening SOURCES");
+
+    for (i, record) in records.iter().enumerate() {
+        if record.direction != Direction::Received || record.frame.len() < 6 {
+            continue;
+        }
+
+        let target = match record.frame[0] {
+            COMMAND_COMPLETE_EVENT | COMMAND_STATUS_EVENT if record.frame[1] == 0 => "response",
+            _ => "event",
+        };
+
+        let dir = corpus.join(target);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{}-{}", name, i));
+        fs::write(&path, &record.frame).unwrap();
+        writeln!(
+            sources,
+            "{}/{}-{}: {}, kernel {}, {}",
+            target,
+            name,
+            i,
+            capture.file_name().unwrap().to_string_lossy(),
+            args[3],
+            args[4]
+        )
+        .unwrap();
+        println!("{}", path.display());
+    }
+}
@@ -0,0 +1,27 @@
+extern crate btmgmt;
+
+use btmgmt::{Command, Error, Params};
+
+// Read Management Version Information, which BTMgmt has no method for
+struct ReadVersion;
+
+impl Command for ReadVersion {
+    type Response = (u8, u16);
+
+    fn opcode(&self) -> u16 {
+        0x0001
+    }
+    fn ctrl_index(&self) -> u16 {
+        0xffff
+    }
+    fn encode(&self, _buffer: &mut Vec<u8>) {}
+    fn decode(&self, params: &mut Params) -> Result<(u8, u16), Error> {
+        Ok((params.u8()?, params.u16()?))
+    }
+}
+
+fn main() {
+    let btmgmt = btmgmt::BTMgmt::new().expect("error opening bt mgmt socket");
+    let (version, revision) = btmgmt.execute(ReadVersion).unwrap();
+    println!("mgmt version {}.{}", version, revision);
+}
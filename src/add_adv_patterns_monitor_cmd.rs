@@ -0,0 +1,61 @@
+use adv_monitor::AdvMonitor;
+use error::Error;
+
+use std::time;
+
+pub const ADD_ADV_PATTERNS_MONITOR_OPCODE: u16 = 0x0052;
+pub const ADD_ADV_PATTERNS_MONITOR_RSSI_OPCODE: u16 = 0x0056;
+
+command! {
+    pub struct AddAdvPatternsMonitorCommand(opcode) -> u16 {
+        |params| params.u16()
+    }
+}
+
+impl AddAdvPatternsMonitorCommand {
+    /// Uses Add Advertisement Patterns Monitor With RSSI Threshold if the monitor has
+    /// thresholds set.
+    pub fn new(
+        ctrl_index: u16,
+        monitor: &AdvMonitor,
+        timeout: time::Duration,
+    ) -> Result<AddAdvPatternsMonitorCommand, Error> {
+        monitor.validate()?;
+        let opcode = match monitor.rssi {
+            Some(_) => ADD_ADV_PATTERNS_MONITOR_RSSI_OPCODE,
+            None => ADD_ADV_PATTERNS_MONITOR_OPCODE,
+        };
+
+        Ok(AddAdvPatternsMonitorCommand::request(
+            ctrl_index,
+            opcode,
+            monitor.to_bytes(),
+            timeout,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adv_monitor::RssiThresholds;
+    use cmd::Command;
+
+    #[test]
+    fn opcode() {
+        let timeout = time::Duration::from_secs(1);
+        let monitor = AdvMonitor::new().pattern(0x03, 0, &[0xaa, 0xfe]);
+        let c = AddAdvPatternsMonitorCommand::new(0, &monitor, timeout).unwrap();
+        assert_eq!(ADD_ADV_PATTERNS_MONITOR_OPCODE, c.opcode());
+
+        let monitor = monitor.rssi(RssiThresholds {
+            high_threshold: -60,
+            high_threshold_timeout: 2,
+            low_threshold: -80,
+            low_threshold_timeout: 5,
+            sampling_period: 0,
+        });
+        let c = AddAdvPatternsMonitorCommand::new(0, &monitor, timeout).unwrap();
+        assert_eq!(ADD_ADV_PATTERNS_MONITOR_RSSI_OPCODE, c.opcode());
+    }
+}
@@ -0,0 +1,45 @@
+use address::{Address, AddressType};
+use error::Error;
+use oob::OobData;
+
+use std::time;
+
+pub const ADD_REMOTE_OOB_DATA_OPCODE: u16 = 0x0021;
+
+command! {
+    pub struct AddRemoteOobDataCommand(ADD_REMOTE_OOB_DATA_OPCODE, address) -> Address {
+        |params| params.address()
+    }
+}
+
+impl AddRemoteOobDataCommand {
+    /// At least one set of values is required, and LE devices only take P-256 values.
+    pub fn new(
+        ctrl_index: u16,
+        address: &Address,
+        data: &OobData,
+        timeout: time::Duration,
+    ) -> Result<AddRemoteOobDataCommand, Error> {
+        if data.p192.is_none() && data.p256.is_none() {
+            return Err(Error::InvalidParameters);
+        }
+
+        match address.address_type {
+            AddressType::LePublic | AddressType::LeRandom if data.p192.is_some() => {
+                return Err(Error::InvalidParameters);
+            }
+            _ => {}
+        }
+
+        let mut oob_data = data.to_bytes();
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&address.address);
+        params.push(address.address_type.to_param());
+        params.append(&mut oob_data);
+
+        Ok(AddRemoteOobDataCommand::request(
+            ctrl_index, address, params, timeout,
+        ))
+    }
+}
@@ -0,0 +1,30 @@
+use class_of_device::ClassOfDevice;
+use uuid::Uuid;
+
+use std::time;
+
+pub const ADD_UUID_OPCODE: u16 = 0x0010;
+
+command! {
+    pub struct AddUuidCommand(ADD_UUID_OPCODE) -> ClassOfDevice {
+        |params| params.decode("class of device", ClassOfDevice::from_bytes)
+    }
+}
+
+impl AddUuidCommand {
+    /// `svc_hint` holds the `SERVICE_CLASS_*` bits the UUID contributes to the class of
+    /// device.
+    pub fn new(
+        ctrl_index: u16,
+        uuid: &Uuid,
+        svc_hint: u8,
+        timeout: time::Duration,
+    ) -> AddUuidCommand {
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&uuid.to_le_bytes());
+        params.push(svc_hint);
+
+        AddUuidCommand::request(ctrl_index, params, timeout)
+    }
+}
@@ -1,6 +1,7 @@
 use hex;
+use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AddressType {
     BrEdr,
     LePublic,
@@ -26,9 +27,18 @@ impl AddressType {
             AddressType::Unknown => 0xff,
         }
     }
+
+    /// The type as sent in command parameters. `Unknown` has no encoding of its own and is
+    /// sent as BR/EDR.
+    pub(crate) fn to_param(&self) -> u8 {
+        match self {
+            AddressType::Unknown => 0,
+            other => other.to_byte(),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Address {
     pub address: [u8; 6],
     pub address_type: AddressType,
@@ -67,30 +77,19 @@ impl Address {
             address_type,
         })
     }
+}
 
-    pub fn to_string(&self) -> String {
-        let mut a0: [u8; 1] = Default::default();
-        let mut a1: [u8; 1] = Default::default();
-        let mut a2: [u8; 1] = Default::default();
-        let mut a3: [u8; 1] = Default::default();
-        let mut a4: [u8; 1] = Default::default();
-        let mut a5: [u8; 1] = Default::default();
-
-        a0.copy_from_slice(&self.address[5..6]);
-        a1.copy_from_slice(&self.address[4..5]);
-        a2.copy_from_slice(&self.address[3..4]);
-        a3.copy_from_slice(&self.address[2..3]);
-        a4.copy_from_slice(&self.address[1..2]);
-        a5.copy_from_slice(&self.address[0..1]);
-
-        format!(
-            "{}:{}:{}:{}:{}:{}",
-            hex::encode_upper(a0),
-            hex::encode_upper(a1),
-            hex::encode_upper(a2),
-            hex::encode_upper(a3),
-            hex::encode_upper(a4),
-            hex::encode_upper(a5)
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+            self.address[5],
+            self.address[4],
+            self.address[3],
+            self.address[2],
+            self.address[1],
+            self.address[0]
         )
     }
 }
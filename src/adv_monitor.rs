@@ -0,0 +1,316 @@
+use address::Address;
+use error::Error;
+use frame::{le16, le32};
+
+// supported and enabled features of Read Advertisement Monitor Features
+pub const ADV_MONITOR_FEATURE_OR_PATTERNS: u32 = 1 << 0;
+
+/// Maximum length of the advertising data a pattern is matched against.
+pub const ADV_PATTERN_MAX_LENGTH: usize = 31;
+
+// RSSI thresholds in dBm and their timeouts in seconds accepted by the kernel
+pub const RSSI_THRESHOLD_MIN: i8 = -127;
+pub const RSSI_THRESHOLD_MAX: i8 = 20;
+pub const RSSI_THRESHOLD_TIMEOUT_MAX: u16 = 300;
+
+/// Matches advertisements carrying an AD structure of type `ad_type` whose data contains
+/// `value` at `offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdvPattern {
+    pub ad_type: u8,
+    pub offset: u8,
+    pub value: Vec<u8>,
+}
+
+impl AdvPattern {
+    pub fn new(ad_type: u8, offset: u8, value: &[u8]) -> AdvPattern {
+        AdvPattern {
+            ad_type,
+            offset,
+            value: value.to_vec(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.value.is_empty() || self.offset as usize + self.value.len() > ADV_PATTERN_MAX_LENGTH
+        {
+            return Err(Error::InvalidParameters);
+        }
+
+        Ok(())
+    }
+
+    // the value is always transferred as a zero padded 31 byte field
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut v = vec![self.ad_type, self.offset, self.value.len() as u8];
+        v.extend_from_slice(&self.value);
+        v.resize(3 + ADV_PATTERN_MAX_LENGTH, 0);
+
+        v
+    }
+}
+
+/// RSSI thresholds in dBm with timeouts in seconds. A device is found once its RSSI stays
+/// at or above `high_threshold` for `high_threshold_timeout` and lost once it stays below
+/// `low_threshold` for `low_threshold_timeout`. `sampling_period` is in units of 100 ms,
+/// 0 reports every advertisement and 0xff only the first one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RssiThresholds {
+    pub high_threshold: i8,
+    pub high_threshold_timeout: u16,
+    pub low_threshold: i8,
+    pub low_threshold_timeout: u16,
+    pub sampling_period: u8,
+}
+
+impl RssiThresholds {
+    /// Checks the thresholds and timeouts against the ranges the kernel accepts.
+    pub fn validate(&self) -> Result<(), Error> {
+        let thresholds = RSSI_THRESHOLD_MIN..=RSSI_THRESHOLD_MAX;
+        if !thresholds.contains(&self.high_threshold)
+            || !thresholds.contains(&self.low_threshold)
+            || self.low_threshold > self.high_threshold
+        {
+            return Err(Error::InvalidParameters);
+        }
+
+        let timeouts = 1..=RSSI_THRESHOLD_TIMEOUT_MAX;
+        if !timeouts.contains(&self.high_threshold_timeout)
+            || !timeouts.contains(&self.low_threshold_timeout)
+        {
+            return Err(Error::InvalidParameters);
+        }
+
+        Ok(())
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        vec![
+            self.high_threshold as u8,
+            (self.high_threshold_timeout & 0xff) as u8,
+            (self.high_threshold_timeout >> 8 & 0xff) as u8,
+            self.low_threshold as u8,
+            (self.low_threshold_timeout & 0xff) as u8,
+            (self.low_threshold_timeout >> 8 & 0xff) as u8,
+            self.sampling_period,
+        ]
+    }
+}
+
+/// An advertisement monitor matching any of its patterns, built up with `pattern` and
+/// optionally `rssi`, e.g. `AdvMonitor::new().pattern(0x03, 0, &[0xaa, 0xfe])` for
+/// advertisements listing the 16-bit service UUID 0xfeaa.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AdvMonitor {
+    pub patterns: Vec<AdvPattern>,
+    pub rssi: Option<RssiThresholds>,
+}
+
+impl AdvMonitor {
+    pub fn new() -> AdvMonitor {
+        AdvMonitor {
+            patterns: Vec::new(),
+            rssi: None,
+        }
+    }
+
+    pub fn pattern(mut self, ad_type: u8, offset: u8, value: &[u8]) -> AdvMonitor {
+        self.patterns.push(AdvPattern::new(ad_type, offset, value));
+        self
+    }
+
+    pub fn rssi(mut self, thresholds: RssiThresholds) -> AdvMonitor {
+        self.rssi = Some(thresholds);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.patterns.is_empty() || self.patterns.len() > 0xff {
+            return Err(Error::InvalidParameters);
+        }
+
+        for pattern in &self.patterns {
+            pattern.validate()?;
+        }
+
+        match self.rssi {
+            Some(rssi) => rssi.validate(),
+            None => Ok(()),
+        }
+    }
+
+    /// Encodes the parameters of Add Advertisement Patterns Monitor, preceded by the RSSI
+    /// thresholds if set.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = match self.rssi {
+            Some(rssi) => rssi.to_bytes(),
+            None => Vec::new(),
+        };
+
+        v.push(self.patterns.len() as u8);
+        for pattern in &self.patterns {
+            v.append(&mut pattern.to_bytes());
+        }
+
+        v
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdvMonitorFeatures {
+    pub supported_features: u32,
+    pub enabled_features: u32,
+    pub max_handles: u16,
+    pub max_patterns: u8,
+    pub handles: Vec<u16>,
+}
+
+impl AdvMonitorFeatures {
+    pub fn from_bytes(data: &[u8]) -> Option<AdvMonitorFeatures> {
+        if data.len() < 13 {
+            return None;
+        }
+
+        let count = le16(&data[11..13]) as usize;
+        if data.len() < 13 + count * 2 {
+            return None;
+        }
+
+        Some(AdvMonitorFeatures {
+            supported_features: le32(&data[0..4]),
+            enabled_features: le32(&data[4..8]),
+            max_handles: le16(&data[8..10]),
+            max_patterns: data[10],
+            handles: data[13..13 + count * 2].chunks(2).map(le16).collect(),
+        })
+    }
+}
+
+/// A device matched by a monitor, as reported by the Adv Monitor Device Found event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdvMonitorDevice {
+    pub monitor_handle: u16,
+    pub address: Address,
+    pub rssi: i8,
+    pub flags: u32,
+    pub ad_data: Vec<u8>,
+}
+
+impl AdvMonitorDevice {
+    pub fn from_bytes(data: &[u8]) -> Option<AdvMonitorDevice> {
+        if data.len() < 16 {
+            return None;
+        }
+
+        let length = le16(&data[14..16]) as usize;
+        if data.len() < 16 + length {
+            return None;
+        }
+
+        let mut address: [u8; 6] = Default::default();
+        address.copy_from_slice(&data[2..8]);
+
+        Some(AdvMonitorDevice {
+            monitor_handle: le16(&data[0..2]),
+            address: Address::from_bytes(address, data[8]),
+            rssi: data[9] as i8,
+            flags: le32(&data[10..14]),
+            ad_data: data[16..16 + length].to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding() {
+        let monitor = AdvMonitor::new()
+            .pattern(0x03, 0, &[0xaa, 0xfe])
+            .pattern(0xff, 2, &[0x01])
+            .rssi(RssiThresholds {
+                high_threshold: -60,
+                high_threshold_timeout: 2,
+                low_threshold: -80,
+                low_threshold_timeout: 5,
+                sampling_period: 0,
+            });
+        assert!(monitor.validate().is_ok());
+
+        let bytes = monitor.to_bytes();
+        assert_eq!(7 + 1 + 2 * 34, bytes.len());
+        assert_eq!(
+            &[0xc4, 0x02, 0x00, 0xb0, 0x05, 0x00, 0x00, 0x02],
+            &bytes[0..8]
+        );
+        assert_eq!(&[0x03, 0x00, 0x02, 0xaa, 0xfe, 0x00], &bytes[8..14]);
+        assert_eq!(&[0xff, 0x02, 0x01, 0x01], &bytes[42..46]);
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert!(AdvMonitor::new().validate().is_err());
+        assert!(AdvMonitor::new().pattern(0x09, 0, &[]).validate().is_err());
+        assert!(AdvMonitor::new()
+            .pattern(0x09, 30, &[0x41, 0x42])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_rssi() {
+        let rssi = RssiThresholds {
+            high_threshold: -60,
+            high_threshold_timeout: 2,
+            low_threshold: -80,
+            low_threshold_timeout: 5,
+            sampling_period: 0,
+        };
+        let monitor = |rssi| AdvMonitor::new().pattern(0x03, 0, &[0xaa, 0xfe]).rssi(rssi);
+
+        assert!(monitor(rssi).validate().is_ok());
+        assert!(monitor(RssiThresholds {
+            high_threshold: 20,
+            low_threshold: -127,
+            high_threshold_timeout: 300,
+            low_threshold_timeout: 300,
+            ..rssi
+        })
+        .validate()
+        .is_ok());
+
+        for invalid in &[
+            RssiThresholds {
+                low_threshold: -50,
+                ..rssi
+            },
+            RssiThresholds {
+                high_threshold: 21,
+                ..rssi
+            },
+            RssiThresholds {
+                low_threshold: -128,
+                ..rssi
+            },
+            RssiThresholds {
+                high_threshold_timeout: 0,
+                ..rssi
+            },
+            RssiThresholds {
+                high_threshold_timeout: 301,
+                ..rssi
+            },
+            RssiThresholds {
+                low_threshold_timeout: 0,
+                ..rssi
+            },
+            RssiThresholds {
+                low_threshold_timeout: 301,
+                ..rssi
+            },
+        ] {
+            assert!(monitor(*invalid).validate().is_err());
+        }
+    }
+}
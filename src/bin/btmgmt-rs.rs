@@ -0,0 +1,632 @@
+//! Command-line front end to the library, covering the commands scripts used to run
+//! BlueZ's `btmgmt` for.
+
+extern crate btmgmt;
+
+use btmgmt::address::{Address, AddressType};
+use btmgmt::event::EventPacket;
+use btmgmt::{AddDeviceAction, BTMgmt, Error};
+
+use std::convert::TryFrom;
+use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::process;
+use std::time;
+
+const USAGE: &str = "\
+Usage: btmgmt-rs [--index <hciN>] [--timeout <seconds>] [--json] <command> [args]
+
+Commands:
+  info                                read controller information
+  connections                         list connected devices
+  conn-info <address>                 read RSSI and TX power of a connection
+  add-device <address> [--action <a>] add a device to the accept list
+  remove-device <address>             remove a device from the accept list
+  unpair <address>                    remove the keys of a device
+  scan-params <interval> <window>     set the LE scan parameters, in 0.625 ms units
+  supported-cmds                      list the supported commands and events
+  monitor [--count <n>]               print events until <n> have been seen
+
+Options:
+  -i, --index <hciN>    controller index, 0 by default
+  -t, --timeout <secs>  how long to wait for a response, 1 by default
+  -j, --json            print JSON instead of text
+  --type <type>         address type: bredr (default), le-public or le-random
+  --action <action>     background, allow or auto (default)
+  --count <n>           number of events to print, unlimited by default
+";
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    index: u16,
+    timeout: time::Duration,
+    json: bool,
+    address_type: AddressType,
+    action: AddDeviceAction,
+    count: Option<usize>,
+    command: String,
+    args: Vec<String>,
+}
+
+#[derive(Debug)]
+enum Failure {
+    Usage(String),
+    Library(Error),
+    Output(io::Error),
+}
+
+impl From<Error> for Failure {
+    fn from(err: Error) -> Failure {
+        Failure::Library(err)
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(err: io::Error) -> Failure {
+        Failure::Output(err)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Failure::Usage(ref reason) => write!(f, "{}\n\n{}", reason, USAGE),
+            Failure::Library(ref err) => write!(f, "{}", err),
+            Failure::Output(ref err) => write!(f, "writing output failed: {}", err),
+        }
+    }
+}
+
+fn usage<T>(reason: &str) -> Result<T, Failure> {
+    Err(Failure::Usage(reason.to_string()))
+}
+
+/// A JSON value, enough for the output of the commands.
+enum Json {
+    Null,
+    Number(i64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn string<T: fmt::Display>(value: T) -> Json {
+        Json::String(value.to_string())
+    }
+
+    fn optional<T: Into<i64>>(value: Option<T>) -> Json {
+        value.map_or(Json::Null, |v| Json::Number(v.into()))
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Json::Null => f.write_str("null"),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(ref s) => {
+                f.write_str("\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' => f.write_str("\\\"")?,
+                        '\\' => f.write_str("\\\\")?,
+                        '\n' => f.write_str("\\n")?,
+                        c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                f.write_str("\"")
+            }
+            Json::Array(ref values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(ref fields) => {
+                f.write_str("{")?;
+                for (i, &(key, ref value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}:{}", Json::string(key), value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+fn parse_number<T>(value: &str, what: &str) -> Result<T, Failure>
+where
+    T: TryFrom<u64>,
+{
+    let parsed = if value.starts_with("0x") || value.starts_with("0X") {
+        u64::from_str_radix(&value[2..], 16).ok()
+    } else {
+        value.parse::<u64>().ok()
+    };
+
+    match parsed.and_then(|n| T::try_from(n).ok()) {
+        Some(n) => Ok(n),
+        None => usage(&format!("invalid {} '{}'", what, value)),
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, Failure> {
+    let mut options = Options {
+        index: 0,
+        timeout: time::Duration::from_secs(1),
+        json: false,
+        address_type: AddressType::BrEdr,
+        action: AddDeviceAction::AutoConnect,
+        count: None,
+        command: String::new(),
+        args: Vec::new(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| match iter.next() {
+            Some(value) => Ok(value.clone()),
+            None => usage(&format!("{} needs a value", name)),
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => {
+                options.command = "help".to_string();
+                return Ok(options);
+            }
+            "-j" | "--json" => options.json = true,
+            "-i" | "--index" => {
+                let index = value(arg)?;
+                let number = index.trim_start_matches("hci");
+                options.index = parse_number(number, "controller index")?;
+            }
+            "-t" | "--timeout" => {
+                let timeout = value(arg)?;
+                options.timeout = match timeout.parse::<f64>() {
+                    Ok(secs) if secs.is_finite() && secs > 0.0 => {
+                        time::Duration::from_secs_f64(secs)
+                    }
+                    _ => return usage(&format!("invalid timeout '{}'", timeout)),
+                };
+            }
+            "--type" => {
+                options.address_type = match value(arg)?.as_str() {
+                    "bredr" | "0" => AddressType::BrEdr,
+                    "le-public" | "1" => AddressType::LePublic,
+                    "le-random" | "2" => AddressType::LeRandom,
+                    other => return usage(&format!("invalid address type '{}'", other)),
+                };
+            }
+            "--action" => {
+                options.action = match value(arg)?.as_str() {
+                    "background" | "0" => AddDeviceAction::BackgroundScan,
+                    "allow" | "1" => AddDeviceAction::AllowIncoming,
+                    "auto" | "2" => AddDeviceAction::AutoConnect,
+                    other => return usage(&format!("invalid action '{}'", other)),
+                };
+            }
+            "--count" => options.count = Some(parse_number(&value(arg)?, "count")?),
+            option if option.starts_with('-') && option.len() > 1 => {
+                return usage(&format!("unknown option '{}'", option));
+            }
+            _ if options.command.is_empty() => options.command = arg.clone(),
+            _ => options.args.push(arg.clone()),
+        }
+    }
+
+    if options.command.is_empty() {
+        return usage("no command given");
+    }
+
+    Ok(options)
+}
+
+fn address_type_name(address_type: &AddressType) -> &'static str {
+    match *address_type {
+        AddressType::BrEdr => "bredr",
+        AddressType::LePublic => "le-public",
+        AddressType::LeRandom => "le-random",
+        AddressType::Unknown => "unknown",
+    }
+}
+
+fn address_json(address: &Address) -> Json {
+    Json::Object(vec![
+        ("address", Json::string(address)),
+        (
+            "type",
+            Json::string(address_type_name(&address.address_type)),
+        ),
+    ])
+}
+
+fn optional_text<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "unknown".to_string(), |v| v.to_string())
+}
+
+impl Options {
+    fn expect_args(&self, count: usize, names: &str) -> Result<(), Failure> {
+        if self.args.len() != count {
+            return usage(&format!("{} expects {}", self.command, names));
+        }
+
+        Ok(())
+    }
+
+    fn address(&self) -> Result<Address, Failure> {
+        self.expect_args(1, "<address>")?;
+        match Address::from_string(&self.args[0], self.address_type.clone()) {
+            Some(address) => Ok(address),
+            None => usage(&format!("invalid address '{}'", self.args[0])),
+        }
+    }
+}
+
+/// Runs the command given by `options` and writes its result to `out`, one JSON document
+/// per line with `--json`.
+fn run(options: &Options, btmgmt: &BTMgmt, out: &mut dyn Write) -> Result<(), Failure> {
+    let index = options.index;
+
+    let (json, text) = match options.command.as_str() {
+        "info" => {
+            options.expect_args(0, "no arguments")?;
+            let info = btmgmt.read_controller_info(index)?;
+            let class = info.class_of_device.to_bytes();
+            let class = u32::from(class[0]) | u32::from(class[1]) << 8 | u32::from(class[2]) << 16;
+            let supported = info.supported_settings.names();
+            let current = info.current_settings.names();
+
+            let text = format!(
+                "hci{}:\taddr {} version {} manufacturer {} class 0x{:06x}\n\
+                 \tsupported settings: {}\n\
+                 \tcurrent settings: {}\n\
+                 \tname {}\n\
+                 \tshort name {}",
+                index,
+                info.address,
+                info.bluetooth_version,
+                info.manufacturer,
+                class,
+                supported.join(" "),
+                current.join(" "),
+                info.name.name,
+                info.name.short_name
+            );
+            let json = Json::Object(vec![
+                ("index", Json::Number(index.into())),
+                ("address", Json::string(&info.address)),
+                (
+                    "bluetooth_version",
+                    Json::Number(info.bluetooth_version.into()),
+                ),
+                ("manufacturer", Json::Number(info.manufacturer.into())),
+                ("class_of_device", Json::Number(class.into())),
+                (
+                    "supported_settings",
+                    Json::Array(supported.into_iter().map(Json::string).collect()),
+                ),
+                (
+                    "current_settings",
+                    Json::Array(current.into_iter().map(Json::string).collect()),
+                ),
+                ("name", Json::string(&info.name.name)),
+                ("short_name", Json::string(&info.name.short_name)),
+            ]);
+            (Some(json), text)
+        }
+        "connections" => {
+            options.expect_args(0, "no arguments")?;
+            let connections = btmgmt.get_connections(index)?;
+
+            let text = if connections.is_empty() {
+                "No connections".to_string()
+            } else {
+                connections
+                    .iter()
+                    .map(|a| format!("{} type {}", a, address_type_name(&a.address_type)))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            };
+            let json = Json::Array(connections.iter().map(address_json).collect());
+            (Some(json), text)
+        }
+        "conn-info" => {
+            let info = btmgmt.get_connection_info(index, &options.address()?)?;
+
+            let text = format!(
+                "Connection Information for {} ({}): RSSI {}, TX power {}, maximum TX power {}",
+                info.address,
+                address_type_name(&info.address.address_type),
+                optional_text(info.rssi),
+                optional_text(info.tx_power),
+                optional_text(info.max_tx_power)
+            );
+            let json = Json::Object(vec![
+                ("address", Json::string(&info.address)),
+                (
+                    "type",
+                    Json::string(address_type_name(&info.address.address_type)),
+                ),
+                ("rssi", Json::optional(info.rssi)),
+                ("tx_power", Json::optional(info.tx_power)),
+                ("max_tx_power", Json::optional(info.max_tx_power)),
+            ]);
+            (Some(json), text)
+        }
+        "add-device" | "remove-device" | "unpair" => {
+            let address = options.address()?;
+            let (address, done) = match options.command.as_str() {
+                "add-device" => (btmgmt.add_device(index, &address, options.action)?, "added"),
+                "remove-device" => (btmgmt.remove_device(index, &address)?, "removed"),
+                _ => (btmgmt.unpair_device(index, &address)?, "unpaired"),
+            };
+
+            let text = format!(
+                "Device {}: {} ({})",
+                done,
+                address,
+                address_type_name(&address.address_type)
+            );
+            (Some(address_json(&address)), text)
+        }
+        "scan-params" => {
+            options.expect_args(2, "<interval> <window>")?;
+            let interval: u16 = parse_number(&options.args[0], "interval")?;
+            let window: u16 = parse_number(&options.args[1], "window")?;
+            btmgmt.set_scan_params(index, interval, window)?;
+
+            let text = format!(
+                "Scan parameters set: interval 0x{:04x}, window 0x{:04x}",
+                interval, window
+            );
+            let json = Json::Object(vec![
+                ("interval", Json::Number(interval.into())),
+                ("window", Json::Number(window.into())),
+            ]);
+            (Some(json), text)
+        }
+        "supported-cmds" => {
+            options.expect_args(0, "no arguments")?;
+            let supported = btmgmt.get_supported_cmds()?;
+
+            let mut text = format!("{} commands:", supported.cmds().len());
+            for cmd in supported.cmds() {
+                text.push_str(&format!("\n\t0x{:04x}", cmd));
+            }
+            text.push_str(&format!("\n{} events:", supported.events().len()));
+            for event in supported.events() {
+                text.push_str(&format!("\n\t0x{:04x}", event));
+            }
+            let numbers = |codes: &[u16]| {
+                Json::Array(codes.iter().map(|&c| Json::Number(c.into())).collect())
+            };
+            let json = Json::Object(vec![
+                ("commands", numbers(supported.cmds())),
+                ("events", numbers(supported.events())),
+            ]);
+            (Some(json), text)
+        }
+        "monitor" => {
+            options.expect_args(0, "no arguments")?;
+            return monitor(options, btmgmt, out);
+        }
+        other => return usage(&format!("unknown command '{}'", other)),
+    };
+
+    match json {
+        Some(ref json) if options.json => writeln!(out, "{}", json)?,
+        _ => writeln!(out, "{}", text)?,
+    }
+
+    Ok(())
+}
+
+fn monitor(options: &Options, btmgmt: &BTMgmt, out: &mut dyn Write) -> Result<(), Failure> {
+    let mut seen = 0;
+    while options.count.is_none_or(|count| seen < count) {
+        let packet: EventPacket = match btmgmt.read_event(time::Duration::from_secs(1))? {
+            Some(packet) => packet,
+            None => continue,
+        };
+
+        if options.json {
+            let json = Json::Object(vec![
+                ("index", Json::Number(packet.controller_index.into())),
+                ("code", Json::Number(packet.event_code.into())),
+                ("event", Json::string(format!("{:?}", packet.event))),
+            ]);
+            writeln!(out, "{}", json)?;
+        } else {
+            writeln!(
+                out,
+                "hci{} event 0x{:04x}: {:?}",
+                packet.controller_index, packet.event_code, packet.event
+            )?;
+        }
+        out.flush()?;
+        seen += 1;
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(failure) => {
+            eprintln!("{}", failure);
+            process::exit(2);
+        }
+    };
+
+    if options.command == "help" {
+        print!("{}", USAGE);
+        return;
+    }
+
+    let mut btmgmt = match BTMgmt::new() {
+        Ok(btmgmt) => btmgmt,
+        Err(err) => {
+            eprintln!("opening the mgmt socket failed: {}", err);
+            process::exit(1);
+        }
+    };
+    btmgmt.set_timeout(options.timeout);
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match run(&options, &btmgmt, &mut out) {
+        Ok(()) => {}
+        Err(failure @ Failure::Usage(_)) => {
+            eprintln!("{}", failure);
+            process::exit(2);
+        }
+        Err(failure) => {
+            eprintln!("{}", failure);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use btmgmt::simulated::{self, SimulatedTransport};
+    use btmgmt::{BTMGMT_CMD_OPCODE_READ_CONTROLLER_INFO, BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED};
+
+    const GET_CONNECTIONS_OPCODE: u16 = 0x0015;
+    const UNPAIR_DEVICE_OPCODE: u16 = 0x001b;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    // answers the commands the tests run, everything else fails with Not Supported
+    fn kernel() -> SimulatedTransport {
+        SimulatedTransport::new(|opcode, ctrl_index, params| {
+            let reply = match opcode {
+                BTMGMT_CMD_OPCODE_READ_CONTROLLER_INFO => {
+                    let mut info = vec![0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xc1, 0x09, 0x02, 0x00];
+                    info.extend_from_slice(&[0x01, 0x02, 0x00, 0x00]);
+                    info.extend_from_slice(&[0x01, 0x00, 0x00, 0x00]);
+                    info.extend_from_slice(&[0x0c, 0x01, 0x00]);
+                    let mut name = [0u8; 260];
+                    name[0..4].copy_from_slice(b"test");
+                    info.extend_from_slice(&name);
+                    return vec![simulated::command_complete(opcode, ctrl_index, 0, &info)];
+                }
+                GET_CONNECTIONS_OPCODE => {
+                    vec![0x01, 0x00, 0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xc1, 0x02]
+                }
+                UNPAIR_DEVICE_OPCODE => params[0..7].to_vec(),
+                _ => return vec![simulated::command_status(opcode, ctrl_index, 0x0c)],
+            };
+
+            vec![simulated::command_complete(opcode, ctrl_index, 0, &reply)]
+        })
+    }
+
+    fn run_line(transport: &SimulatedTransport, line: &str) -> Result<String, Failure> {
+        let options = parse_args(&args(line))?;
+        let mut btmgmt = BTMgmt::with_transport(Box::new(transport.clone()));
+        btmgmt.set_timeout(options.timeout);
+
+        let mut out = Vec::new();
+        run(&options, &btmgmt, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = parse_args(&args(
+            "-i hci1 conn-info --json C1:BC:CD:DE:EF:F1 -t 0.5 --type le-random",
+        ))
+        .unwrap();
+        assert_eq!(1, options.index);
+        assert_eq!(time::Duration::from_millis(500), options.timeout);
+        assert!(options.json);
+        assert_eq!(AddressType::LeRandom, options.address_type);
+        assert_eq!("conn-info", options.command);
+        assert_eq!(vec!["C1:BC:CD:DE:EF:F1".to_string()], options.args);
+
+        assert!(matches!(parse_args(&[]), Err(Failure::Usage(_))));
+        assert!(matches!(
+            parse_args(&args("info --bogus")),
+            Err(Failure::Usage(_))
+        ));
+        assert!(matches!(
+            parse_args(&args("info -t 0")),
+            Err(Failure::Usage(_))
+        ));
+        assert!(matches!(
+            parse_args(&args("info -i 65536")),
+            Err(Failure::Usage(_))
+        ));
+        assert!(matches!(
+            parse_args(&args("info -i")),
+            Err(Failure::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn runs_commands() {
+        let transport = kernel();
+
+        assert_eq!(
+            "hci0:\taddr C1:BC:CD:DE:EF:F1 version 9 manufacturer 2 class 0x00010c\n\
+             \tsupported settings: powered le\n\
+             \tcurrent settings: powered\n\
+             \tname test\n\
+             \tshort name \n",
+            run_line(&transport, "info").unwrap()
+        );
+        assert_eq!(
+            "[{\"address\":\"C1:BC:CD:DE:EF:F1\",\"type\":\"le-random\"}]\n",
+            run_line(&transport, "connections --json").unwrap()
+        );
+        assert_eq!(
+            "Device unpaired: C1:BC:CD:DE:EF:F1 (le-public)\n",
+            run_line(&transport, "unpair C1:BC:CD:DE:EF:F1 --type le-public").unwrap()
+        );
+
+        match run_line(&transport, "--index 1 scan-params 0x60 0x30") {
+            Err(Failure::Library(err)) => assert_eq!(
+                "opcode 0x002c on index 1 failed: Not supported (0x0c)",
+                err.to_string()
+            ),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(matches!(
+            run_line(&transport, "conn-info nonsense"),
+            Err(Failure::Usage(_))
+        ));
+        assert!(matches!(
+            run_line(&transport, "frobnicate"),
+            Err(Failure::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn monitors_events() {
+        let transport = kernel();
+        transport.inject(simulated::event(
+            BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED,
+            0,
+            &[0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xc1, 0x02, 0x03],
+        ));
+
+        let output = run_line(&transport, "monitor --count 1 --json").unwrap();
+        assert!(output.starts_with("{\"index\":0,\"code\":12,\"event\":\"DeviceDisconnected"));
+        assert_eq!(1, output.lines().count());
+    }
+}
@@ -0,0 +1,279 @@
+//! Capture of mgmt traffic in the btsnoop format written by `btmon -w`, which Wireshark and
+//! `btmon -r` open. Commands are stored as monitor Control Command records and frames
+//! received from the kernel as Control Event records.
+
+use error::Error;
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+const MAGIC: &[u8; 8] = b"btsnoop\0";
+const VERSION: u32 = 1;
+/// Datalink type of captures in the HCI monitor format.
+const DATALINK_MONITOR: u32 = 2001;
+/// Microseconds between 0000-01-01, the btsnoop epoch, and the unix epoch.
+const EPOCH_OFFSET_US: u64 = 0x00dc_ddb3_0f2f_8000;
+
+const OPCODE_CTRL_COMMAND: u16 = 16;
+const OPCODE_CTRL_EVENT: u16 = 17;
+
+const HEADER_SIZE: usize = 16;
+const RECORD_HEADER_SIZE: usize = 24;
+const MGMT_HEADER_SIZE: usize = 6;
+/// Socket cookie stored in the records, the library does not know the kernel's.
+const COOKIE: u32 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// A command written to the kernel.
+    Sent,
+    /// A response or event read from the kernel.
+    Received,
+}
+
+/// An mgmt frame read back from a capture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub timestamp: time::SystemTime,
+    pub direction: Direction,
+    /// The frame as exchanged with the kernel, including the mgmt header.
+    pub frame: Vec<u8>,
+}
+
+struct TracerState {
+    writer: Box<dyn Write + Send>,
+    enabled: bool,
+    error: Option<io::Error>,
+}
+
+/// Writes mgmt frames to a capture. Clones share the same capture, so one tracer can be set
+/// on `BTMgmt` and the event listener at once.
+#[derive(Clone)]
+pub struct Tracer {
+    state: Arc<Mutex<TracerState>>,
+}
+
+impl Tracer {
+    /// Creates a capture file at `path`, replacing an existing one.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Tracer, Error> {
+        Tracer::new(BufWriter::new(File::create(path)?))
+    }
+
+    /// Starts a capture on `writer` by writing the btsnoop header. The tracer is enabled.
+    pub fn new<W: Write + Send + 'static>(mut writer: W) -> Result<Tracer, Error> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_be_bytes())?;
+        writer.write_all(&DATALINK_MONITOR.to_be_bytes())?;
+
+        Ok(Tracer {
+            state: Arc::new(Mutex::new(TracerState {
+                writer: Box::new(writer),
+                enabled: true,
+                error: None,
+            })),
+        })
+    }
+
+    /// Pauses or resumes recording without closing the capture.
+    pub fn set_enabled(&self, enabled: bool) {
+        if let Ok(mut state) = self.state.lock() {
+            state.enabled = enabled;
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state
+            .lock()
+            .map(|state| state.enabled)
+            .unwrap_or(false)
+    }
+
+    /// Flushes the capture and reports the first error hit while recording, which stops
+    /// further recording.
+    pub fn flush(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().map_err(|_| Error::UnknownError)?;
+        if let Some(err) = state.error.take() {
+            return Err(Error::Io(err));
+        }
+
+        state.writer.flush()?;
+        Ok(())
+    }
+
+    /// Records a frame. Frames shorter than the mgmt header are skipped, failures are kept
+    /// for `flush` so tracing never fails the traffic it records.
+    pub fn record(&self, direction: Direction, frame: &[u8]) {
+        if frame.len() < MGMT_HEADER_SIZE {
+            return;
+        }
+
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        if !state.enabled || state.error.is_some() {
+            return;
+        }
+
+        if let Err(err) = write_record(&mut state.writer, direction, frame) {
+            state.error = Some(err);
+        }
+    }
+}
+
+fn write_record(writer: &mut dyn Write, direction: Direction, frame: &[u8]) -> io::Result<()> {
+    let opcode = match direction {
+        Direction::Sent => OPCODE_CTRL_COMMAND,
+        Direction::Received => OPCODE_CTRL_EVENT,
+    };
+    let ctrl_index = u32::from(frame[2]) | (u32::from(frame[3]) << 8);
+
+    let mut data = Vec::with_capacity(6 + frame.len() - MGMT_HEADER_SIZE);
+    data.extend_from_slice(&COOKIE.to_le_bytes());
+    data.extend_from_slice(&frame[0..2]);
+    data.extend_from_slice(&frame[MGMT_HEADER_SIZE..]);
+
+    let timestamp = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+        + EPOCH_OFFSET_US;
+
+    let mut header = Vec::with_capacity(RECORD_HEADER_SIZE);
+    header.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    header.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    header.extend_from_slice(&((ctrl_index << 16) | u32::from(opcode)).to_be_bytes());
+    header.extend_from_slice(&0u32.to_be_bytes());
+    header.extend_from_slice(&timestamp.to_be_bytes());
+
+    writer.write_all(&header)?;
+    writer.write_all(&data)
+}
+
+/// Reads back the mgmt frames of a capture file, see `read`.
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Vec<Record>, Error> {
+    read(BufReader::new(File::open(path)?))
+}
+
+/// Reads back the mgmt frames of a capture. Records of other monitor packets, e.g. HCI
+/// traffic captured by `btmon`, are skipped.
+pub fn read<R: Read>(mut reader: R) -> Result<Vec<Record>, Error> {
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    if &header[0..8] != MAGIC || be32(&header[8..12]) != VERSION {
+        return Err(invalid("not a btsnoop capture"));
+    }
+
+    if be32(&header[12..16]) != DATALINK_MONITOR {
+        return Err(invalid("not a monitor capture"));
+    }
+
+    let mut records = Vec::new();
+    loop {
+        let mut header = [0u8; RECORD_HEADER_SIZE];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(Error::Io(err)),
+        }
+
+        let mut data = vec![0u8; be32(&header[4..8]) as usize];
+        reader.read_exact(&mut data)?;
+
+        let flags = be32(&header[8..12]);
+        let direction = match flags as u16 {
+            OPCODE_CTRL_COMMAND => Direction::Sent,
+            OPCODE_CTRL_EVENT => Direction::Received,
+            _ => continue,
+        };
+
+        if data.len() < 6 || data.len() - 6 > 0xffff {
+            return Err(invalid("truncated control record"));
+        }
+
+        let ctrl_index = (flags >> 16) as u16;
+        let param_length = (data.len() - 6) as u16;
+        let mut frame = Vec::with_capacity(MGMT_HEADER_SIZE + data.len() - 6);
+        frame.extend_from_slice(&data[4..6]);
+        frame.extend_from_slice(&ctrl_index.to_le_bytes());
+        frame.extend_from_slice(&param_length.to_le_bytes());
+        frame.extend_from_slice(&data[6..]);
+
+        let micros = u64::from(be32(&header[16..20])) << 32 | u64::from(be32(&header[20..24]));
+        records.push(Record {
+            timestamp: time::UNIX_EPOCH
+                + time::Duration::from_micros(micros.saturating_sub(EPOCH_OFFSET_US)),
+            direction,
+            frame,
+        });
+    }
+
+    Ok(records)
+}
+
+fn be32(data: &[u8]) -> u32 {
+    u32::from(data[0]) << 24
+        | u32::from(data[1]) << 16
+        | u32::from(data[2]) << 8
+        | u32::from(data[3])
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::InvalidData, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // collects the capture in memory
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let buffer = Buffer::default();
+        let tracer = Tracer::new(buffer.clone()).unwrap();
+        let start = time::SystemTime::now();
+
+        let command = [0x48, 0x00, 0x01, 0x00, 0x00, 0x00];
+        let response = [
+            0x01, 0x00, 0x01, 0x00, 0x05, 0x00, 0x48, 0x00, 0x00, 0x00, 0x00,
+        ];
+        tracer.record(Direction::Sent, &command);
+        tracer.set_enabled(false);
+        tracer.record(Direction::Received, &[0x06, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        tracer.set_enabled(true);
+        tracer.record(Direction::Received, &response);
+        tracer.flush().unwrap();
+
+        let data = buffer.0.lock().unwrap().clone();
+        assert_eq!(b"btsnoop\0", &data[0..8]);
+
+        let records = read(&data[..]).unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(Direction::Sent, records[0].direction);
+        assert_eq!(&command[..], &records[0].frame[..]);
+        assert_eq!(Direction::Received, records[1].direction);
+        assert_eq!(&response[..], &records[1].frame[..]);
+        assert!(records[0].timestamp + time::Duration::from_secs(1) > start);
+
+        assert!(read(&data[0..20]).unwrap().is_empty());
+        assert!(read(&data[0..43]).is_err());
+        assert!(read(&b"btsnoop\0\0\0\0\x01\0\0\x03\xe9"[..]).is_err());
+    }
+}
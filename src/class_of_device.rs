@@ -0,0 +1,186 @@
+use error::Error;
+
+// service class bits of the class of device, also used as the service hint of a UUID
+pub const SERVICE_CLASS_POSITIONING: u8 = 0x01;
+pub const SERVICE_CLASS_NETWORKING: u8 = 0x02;
+pub const SERVICE_CLASS_RENDERING: u8 = 0x04;
+pub const SERVICE_CLASS_CAPTURING: u8 = 0x08;
+pub const SERVICE_CLASS_OBJECT_TRANSFER: u8 = 0x10;
+pub const SERVICE_CLASS_AUDIO: u8 = 0x20;
+pub const SERVICE_CLASS_TELEPHONY: u8 = 0x40;
+pub const SERVICE_CLASS_INFORMATION: u8 = 0x80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MajorDeviceClass {
+    Miscellaneous,
+    Computer,
+    Phone,
+    NetworkAccessPoint,
+    AudioVideo,
+    Peripheral,
+    Imaging,
+    Wearable,
+    Toy,
+    Health,
+    Uncategorized,
+    /// A reserved major class, holding its 5-bit value so it is encoded unchanged.
+    Other(u8),
+}
+
+impl MajorDeviceClass {
+    pub fn from_byte(value: u8) -> MajorDeviceClass {
+        match value {
+            0x00 => MajorDeviceClass::Miscellaneous,
+            0x01 => MajorDeviceClass::Computer,
+            0x02 => MajorDeviceClass::Phone,
+            0x03 => MajorDeviceClass::NetworkAccessPoint,
+            0x04 => MajorDeviceClass::AudioVideo,
+            0x05 => MajorDeviceClass::Peripheral,
+            0x06 => MajorDeviceClass::Imaging,
+            0x07 => MajorDeviceClass::Wearable,
+            0x08 => MajorDeviceClass::Toy,
+            0x09 => MajorDeviceClass::Health,
+            0x1f => MajorDeviceClass::Uncategorized,
+            other => MajorDeviceClass::Other(other & 0x1f),
+        }
+    }
+
+    pub fn to_byte(self) -> u8 {
+        match self {
+            MajorDeviceClass::Miscellaneous => 0x00,
+            MajorDeviceClass::Computer => 0x01,
+            MajorDeviceClass::Phone => 0x02,
+            MajorDeviceClass::NetworkAccessPoint => 0x03,
+            MajorDeviceClass::AudioVideo => 0x04,
+            MajorDeviceClass::Peripheral => 0x05,
+            MajorDeviceClass::Imaging => 0x06,
+            MajorDeviceClass::Wearable => 0x07,
+            MajorDeviceClass::Toy => 0x08,
+            MajorDeviceClass::Health => 0x09,
+            MajorDeviceClass::Uncategorized => 0x1f,
+            MajorDeviceClass::Other(value) => value & 0x1f,
+        }
+    }
+}
+
+/// The 24-bit class of device. `minor` is the 6-bit minor device class and
+/// `service_classes` holds the `SERVICE_CLASS_*` bits, which the kernel derives from the
+/// service hints of the registered UUIDs. `format` (bits 0-1) and `reserved` (bits 14-15)
+/// are zero in classes defined by the specification, they are kept so that a class read
+/// from the kernel is encoded unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassOfDevice {
+    pub major: MajorDeviceClass,
+    pub minor: u8,
+    pub limited_discoverable: bool,
+    pub service_classes: u8,
+    pub format: u8,
+    pub reserved: u8,
+}
+
+impl ClassOfDevice {
+    pub fn new(major: MajorDeviceClass, minor: u8) -> Result<ClassOfDevice, Error> {
+        if let MajorDeviceClass::Other(_) = major {
+            return Err(Error::InvalidParameters);
+        }
+
+        if minor > 0x3f {
+            return Err(Error::InvalidParameters);
+        }
+
+        Ok(ClassOfDevice {
+            major,
+            minor,
+            limited_discoverable: false,
+            service_classes: 0,
+            format: 0,
+            reserved: 0,
+        })
+    }
+
+    pub fn with_service_classes(mut self, service_classes: u8) -> ClassOfDevice {
+        self.service_classes = service_classes;
+        self
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<ClassOfDevice> {
+        if data.len() < 3 {
+            return None;
+        }
+
+        Some(ClassOfDevice {
+            major: MajorDeviceClass::from_byte(data[1] & 0x1f),
+            minor: data[0] >> 2,
+            limited_discoverable: data[1] & 0x20 != 0,
+            service_classes: data[2],
+            format: data[0] & 0x03,
+            reserved: data[1] >> 6,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 3] {
+        [
+            self.minor << 2 | (self.format & 0x03),
+            (self.major.to_byte() & 0x1f)
+                | if self.limited_discoverable { 0x20 } else { 0 }
+                | (self.reserved & 0x03) << 6,
+            self.service_classes,
+        ]
+    }
+
+    /// The major class as expected by the Set Device Class command.
+    pub fn major_byte(&self) -> u8 {
+        self.major.to_byte() & 0x1f
+    }
+
+    /// The minor class as expected by the Set Device Class command, i.e. shifted past the
+    /// two format bits.
+    pub fn minor_byte(&self) -> u8 {
+        self.minor << 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        // Computer/Laptop, limited discoverable, networking and object transfer
+        let data = [0x0c, 0x21, 0x12];
+        let class = ClassOfDevice::from_bytes(&data).unwrap();
+        assert_eq!(MajorDeviceClass::Computer, class.major);
+        assert_eq!(0x03, class.minor);
+        assert!(class.limited_discoverable);
+        assert_eq!(
+            SERVICE_CLASS_NETWORKING | SERVICE_CLASS_OBJECT_TRANSFER,
+            class.service_classes
+        );
+        assert_eq!(data, class.to_bytes());
+
+        let class = ClassOfDevice::new(MajorDeviceClass::AudioVideo, 0x01)
+            .unwrap()
+            .with_service_classes(SERVICE_CLASS_AUDIO);
+        assert_eq!([0x04, 0x04, 0x20], class.to_bytes());
+        assert_eq!((0x04, 0x04), (class.major_byte(), class.minor_byte()));
+        assert_eq!(Some(class), ClassOfDevice::from_bytes(&class.to_bytes()));
+
+        // reserved major classes keep their value
+        let class = ClassOfDevice::from_bytes(&[0x00, 0x0b, 0x00]).unwrap();
+        assert_eq!(MajorDeviceClass::Other(0x0b), class.major);
+        assert_eq!([0x00, 0x0b, 0x00], class.to_bytes());
+
+        // so do the format and reserved bits
+        let data = [0x0f, 0xe1, 0x12];
+        let class = ClassOfDevice::from_bytes(&data).unwrap();
+        assert_eq!((0x03, 0x03), (class.format, class.reserved));
+        assert_eq!(MajorDeviceClass::Computer, class.major);
+        assert_eq!(0x03, class.minor);
+        assert_eq!(data, class.to_bytes());
+        assert_eq!(0x0c, class.minor_byte());
+
+        assert!(ClassOfDevice::new(MajorDeviceClass::Other(0x0b), 0).is_err());
+        assert!(ClassOfDevice::new(MajorDeviceClass::Phone, 0x40).is_err());
+        assert!(ClassOfDevice::from_bytes(&[0x00, 0x01]).is_none());
+    }
+}
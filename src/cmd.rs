@@ -1,29 +1,241 @@
+use error::Error;
+use frame::{self, Params};
+
 use std::time;
 
+use {COMMAND_RESPONSE_EVENT, COMMAND_STATUS_EVENT};
+
+/// A mgmt command sent with `BTMgmt::execute`. Besides the commands wrapped by `BTMgmt`,
+/// this allows defining vendor or not yet supported commands outside of this crate.
 pub trait Command {
-    fn get_cmd_code(&self) -> u16;
-    fn get_ctrl_index(&self) -> u16;
-    fn get_param_length(&self) -> u16;
-    fn get_params(&self) -> Vec<u8>;
-    fn is_response(&self, &[u8]) -> bool;
-    fn store_response(&mut self, Vec<u8>);
-    fn get_timeout(&self) -> time::Duration;
-
-    fn to_bytes(&self) -> Vec<u8> {
-        let mut v = vec![
-            (self.get_cmd_code() & 0xff) as u8,
-            ((self.get_cmd_code() >> 8) & 0xff) as u8,
-            (self.get_ctrl_index() & 0xff) as u8,
-            ((self.get_ctrl_index() >> 8) & 0xff) as u8,
-            (self.get_param_length() & 0xff) as u8,
-            ((self.get_param_length() >> 8) & 0xff) as u8,
-        ];
-
-        v.append(&mut self.get_params().clone());
-        v
+    type Response;
+
+    fn opcode(&self) -> u16;
+
+    /// The controller index, or 0xffff for commands not tied to a controller.
+    fn ctrl_index(&self) -> u16;
+
+    /// Appends the command parameters to `buffer`.
+    fn encode(&self, buffer: &mut Vec<u8>);
+
+    /// Decodes the return parameters of a successful Command Complete event.
+    fn decode(&self, params: &mut Params) -> Result<Self::Response, Error>;
+
+    /// Whether the return parameters of a successful Command Complete event belong to
+    /// this command, for commands which may be in flight for several devices at once.
+    fn matches(&self, _params: &[u8]) -> bool {
+        true
+    }
+
+    fn timeout(&self) -> time::Duration {
+        time::Duration::from_secs(1)
+    }
+}
+
+pub(crate) fn to_frame<C: Command + ?Sized>(cmd: &C) -> Result<Vec<u8>, Error> {
+    let mut frame = vec![0; 6];
+    cmd.encode(&mut frame);
+
+    let param_length = frame.len() - 6;
+    if param_length > 0xffff {
+        return Err(Error::InvalidParameters);
+    }
+
+    frame[0] = (cmd.opcode() & 0xff) as u8;
+    frame[1] = (cmd.opcode() >> 8 & 0xff) as u8;
+    frame[2] = (cmd.ctrl_index() & 0xff) as u8;
+    frame[3] = (cmd.ctrl_index() >> 8 & 0xff) as u8;
+    frame[4] = (param_length & 0xff) as u8;
+    frame[5] = (param_length >> 8 & 0xff) as u8;
+
+    Ok(frame)
+}
+
+/// Whether `frame` is the Command Complete or Command Status event answering `cmd`.
+/// Frames which fail or are too short to match on are taken as the response, decoding
+/// reports the error.
+pub(crate) fn is_response<C: Command + ?Sized>(cmd: &C, frame: &[u8]) -> bool {
+    if frame.len() < 8 || (frame[0] != COMMAND_RESPONSE_EVENT && frame[0] != COMMAND_STATUS_EVENT) {
+        return false;
+    }
+
+    let ctrl_index = u16::from(frame[2]) | (u16::from(frame[3]) << 8);
+    let opcode = u16::from(frame[6]) | (u16::from(frame[7]) << 8);
+    if ctrl_index != cmd.ctrl_index() || opcode != cmd.opcode() {
+        return false;
     }
 
-    fn size(&self) -> usize {
-        6 + self.get_params().len()
+    if frame.len() < 9 || frame[8] != 0 {
+        return true;
     }
+
+    cmd.matches(&frame[9..])
+}
+
+pub(crate) fn decode_response<C: Command + ?Sized>(
+    cmd: &C,
+    frame: &[u8],
+) -> Result<C::Response, Error> {
+    cmd.decode(&mut frame::response_params(frame)?)
+}
+
+/// Defines a mgmt command from its opcode and a decoder for its return parameters. This
+/// generates the struct holding the encoded parameters and the `Command` implementation.
+/// The defining module adds a `new` which encodes the parameters and hands them to the
+/// generated `request`:
+///
+/// ```ignore
+/// command! {
+///     pub struct SetAppearanceCommand(SET_APPEARANCE_OPCODE) -> () {
+///         |_| Ok(())
+///     }
+/// }
+///
+/// impl SetAppearanceCommand {
+///     pub fn new(ctrl_index: u16, appearance: u16, timeout: time::Duration) -> Self {
+///         let params = vec![(appearance & 0xff) as u8, (appearance >> 8 & 0xff) as u8];
+///
+///         SetAppearanceCommand::request(ctrl_index, params, timeout)
+///     }
+/// }
+/// ```
+///
+/// Commands whose return parameters start with the address they were sent for are
+/// declared as `Name(OPCODE, address)`, their `request` takes the address as second
+/// argument and only responses for that address are accepted. Declared as `Name(opcode)`,
+/// `request` takes the opcode as second argument instead, for one definition shared by
+/// several commands.
+macro_rules! command {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident(opcode) -> $response:ty {
+            $decode:expr
+        }
+    ) => {
+        $(#[$attr])*
+        pub struct $name {
+            opcode: u16,
+            ctrl_index: u16,
+            params: Vec<u8>,
+            timeout: ::std::time::Duration,
+        }
+
+        impl $name {
+            fn request(
+                ctrl_index: u16,
+                opcode: u16,
+                params: Vec<u8>,
+                timeout: ::std::time::Duration,
+            ) -> $name {
+                $name {
+                    opcode,
+                    ctrl_index,
+                    params,
+                    timeout,
+                }
+            }
+        }
+
+        command!(@impl $name -> $response { $decode });
+    };
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident($opcode:expr, address) -> $response:ty {
+            $decode:expr
+        }
+    ) => {
+        $(#[$attr])*
+        pub struct $name {
+            opcode: u16,
+            ctrl_index: u16,
+            address: $crate::address::Address,
+            params: Vec<u8>,
+            timeout: ::std::time::Duration,
+        }
+
+        impl $name {
+            fn request(
+                ctrl_index: u16,
+                address: &$crate::address::Address,
+                params: Vec<u8>,
+                timeout: ::std::time::Duration,
+            ) -> $name {
+                $name {
+                    opcode: $opcode,
+                    ctrl_index,
+                    address: address.clone(),
+                    params,
+                    timeout,
+                }
+            }
+
+            fn is_for_address(&self, params: &[u8]) -> bool {
+                params.len() < 7
+                    || (params[0..6] == self.address.address
+                        && params[6] == self.address.address_type.to_param())
+            }
+        }
+
+        command!(@impl $name -> $response { $decode } is_for_address);
+    };
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident($opcode:expr) -> $response:ty {
+            $decode:expr
+        }
+    ) => {
+        $(#[$attr])*
+        pub struct $name {
+            opcode: u16,
+            ctrl_index: u16,
+            params: Vec<u8>,
+            timeout: ::std::time::Duration,
+        }
+
+        impl $name {
+            fn request(ctrl_index: u16, params: Vec<u8>, timeout: ::std::time::Duration) -> $name {
+                $name {
+                    opcode: $opcode,
+                    ctrl_index,
+                    params,
+                    timeout,
+                }
+            }
+        }
+
+        command!(@impl $name -> $response { $decode });
+    };
+    (@impl $name:ident -> $response:ty { $decode:expr } $($matches:ident)*) => {
+        impl $crate::cmd::Command for $name {
+            type Response = $response;
+
+            fn opcode(&self) -> u16 {
+                self.opcode
+            }
+            fn ctrl_index(&self) -> u16 {
+                self.ctrl_index
+            }
+            fn encode(&self, buffer: &mut Vec<u8>) {
+                buffer.extend_from_slice(&self.params);
+            }
+            fn decode(
+                &self,
+                params: &mut $crate::frame::Params,
+            ) -> Result<$response, $crate::error::Error> {
+                let decode: fn(
+                    &mut $crate::frame::Params,
+                ) -> Result<$response, $crate::error::Error> = $decode;
+
+                decode(params)
+            }
+            $(
+            fn matches(&self, params: &[u8]) -> bool {
+                self.$matches(params)
+            }
+            )*
+            fn timeout(&self) -> ::std::time::Duration {
+                self.timeout
+            }
+        }
+    };
 }
@@ -0,0 +1,45 @@
+use error::Error;
+use tlv::TlvList;
+
+use std::time;
+
+// the default system and runtime configuration are read and set the same way, as TLV lists
+pub const READ_DEF_SYSTEM_CONFIG_OPCODE: u16 = 0x004B;
+pub const SET_DEF_SYSTEM_CONFIG_OPCODE: u16 = 0x004C;
+pub const READ_DEF_RUNTIME_CONFIG_OPCODE: u16 = 0x004D;
+pub const SET_DEF_RUNTIME_CONFIG_OPCODE: u16 = 0x004E;
+
+command! {
+    /// Read Default System Configuration or Read Default Runtime Configuration.
+    pub struct ReadDefConfigCommand(opcode) -> TlvList {
+        |params| params.decode("TLV list", TlvList::from_bytes)
+    }
+}
+
+impl ReadDefConfigCommand {
+    pub fn new(ctrl_index: u16, opcode: u16, timeout: time::Duration) -> ReadDefConfigCommand {
+        ReadDefConfigCommand::request(ctrl_index, opcode, Vec::new(), timeout)
+    }
+}
+
+command! {
+    /// Set Default System Configuration or Set Default Runtime Configuration.
+    pub struct SetDefConfigCommand(opcode) -> () {
+        |_| Ok(())
+    }
+}
+
+impl SetDefConfigCommand {
+    pub fn new(
+        ctrl_index: u16,
+        opcode: u16,
+        config: &TlvList,
+        timeout: time::Duration,
+    ) -> Result<SetDefConfigCommand, Error> {
+        let params = config.to_bytes()?;
+
+        Ok(SetDefConfigCommand::request(
+            ctrl_index, opcode, params, timeout,
+        ))
+    }
+}
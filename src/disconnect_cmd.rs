@@ -0,0 +1,22 @@
+use address::Address;
+
+use std::time;
+
+pub const DISCONNECT_OPCODE: u16 = 0x0014;
+
+command! {
+    pub struct DisconnectCommand(DISCONNECT_OPCODE, address) -> Address {
+        |params| params.address()
+    }
+}
+
+impl DisconnectCommand {
+    pub fn new(ctrl_index: u16, address: &Address, timeout: time::Duration) -> DisconnectCommand {
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&address.address);
+        params.push(address.address_type.to_param());
+
+        DisconnectCommand::request(ctrl_index, address, params, timeout)
+    }
+}
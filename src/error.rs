@@ -1,11 +1,10 @@
 use std::error::Error as StdError;
 use std::fmt;
+use std::io;
 
-#[derive(Debug)]
-pub enum Error {
-    SocketError,
-    BindError,
-    NoResponse,
+/// Status code of a Command Complete or Command Status event that reports a failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
     UnknownCommand,
     NotConnected,
     Failed,
@@ -13,6 +12,7 @@ pub enum Error {
     AuthenticationFailed,
     NotPaired,
     NoResources,
+    /// The kernel gave up waiting on the controller or the remote device.
     Timeout,
     AlreadyConnected,
     Busy,
@@ -26,95 +26,210 @@ pub enum Error {
     RFKilled,
     AlreadyPaired,
     PermissionDenied,
-    UnknownError,
+    Other(u8),
 }
 
-impl Error {
-    pub fn from_status(status: u8) -> Option<Error> {
+impl Status {
+    /// Returns `None` for 0x00, which reports success.
+    pub fn from_u8(status: u8) -> Option<Status> {
         match status {
             0x00 => None,
-            0x01 => Some(Error::UnknownCommand),
-            0x02 => Some(Error::NotConnected),
-            0x03 => Some(Error::Failed),
-            0x04 => Some(Error::ConnectFailed),
-            0x05 => Some(Error::AuthenticationFailed),
-            0x06 => Some(Error::NotPaired),
-            0x07 => Some(Error::NoResources),
-            0x08 => Some(Error::Timeout),
-            0x09 => Some(Error::AlreadyConnected),
-            0x0A => Some(Error::Busy),
-            0x0B => Some(Error::Rejected),
-            0x0C => Some(Error::NotSupported),
-            0x0D => Some(Error::InvalidParameters),
-            0x0E => Some(Error::Disconnected),
-            0x0F => Some(Error::NotPowered),
-            0x10 => Some(Error::Canceled),
-            0x11 => Some(Error::InvalidIndex),
-            0x12 => Some(Error::RFKilled),
-            0x13 => Some(Error::AlreadyPaired),
-            0x14 => Some(Error::PermissionDenied),
-            _ => Some(Error::UnknownError),
+            0x01 => Some(Status::UnknownCommand),
+            0x02 => Some(Status::NotConnected),
+            0x03 => Some(Status::Failed),
+            0x04 => Some(Status::ConnectFailed),
+            0x05 => Some(Status::AuthenticationFailed),
+            0x06 => Some(Status::NotPaired),
+            0x07 => Some(Status::NoResources),
+            0x08 => Some(Status::Timeout),
+            0x09 => Some(Status::AlreadyConnected),
+            0x0A => Some(Status::Busy),
+            0x0B => Some(Status::Rejected),
+            0x0C => Some(Status::NotSupported),
+            0x0D => Some(Status::InvalidParameters),
+            0x0E => Some(Status::Disconnected),
+            0x0F => Some(Status::NotPowered),
+            0x10 => Some(Status::Canceled),
+            0x11 => Some(Status::InvalidIndex),
+            0x12 => Some(Status::RFKilled),
+            0x13 => Some(Status::AlreadyPaired),
+            0x14 => Some(Status::PermissionDenied),
+            other => Some(Status::Other(other)),
         }
     }
+
+    /// Returns the raw status byte.
+    pub fn code(self) -> u8 {
+        match self {
+            Status::UnknownCommand => 0x01,
+            Status::NotConnected => 0x02,
+            Status::Failed => 0x03,
+            Status::ConnectFailed => 0x04,
+            Status::AuthenticationFailed => 0x05,
+            Status::NotPaired => 0x06,
+            Status::NoResources => 0x07,
+            Status::Timeout => 0x08,
+            Status::AlreadyConnected => 0x09,
+            Status::Busy => 0x0A,
+            Status::Rejected => 0x0B,
+            Status::NotSupported => 0x0C,
+            Status::InvalidParameters => 0x0D,
+            Status::Disconnected => 0x0E,
+            Status::NotPowered => 0x0F,
+            Status::Canceled => 0x10,
+            Status::InvalidIndex => 0x11,
+            Status::RFKilled => 0x12,
+            Status::AlreadyPaired => 0x13,
+            Status::PermissionDenied => 0x14,
+            Status::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Status::UnknownCommand => f.write_str("Unknown command"),
+            Status::NotConnected => f.write_str("Not connected"),
+            Status::Failed => f.write_str("Failed"),
+            Status::ConnectFailed => f.write_str("Connect failed"),
+            Status::AuthenticationFailed => f.write_str("Authentication failed"),
+            Status::NotPaired => f.write_str("Not paired"),
+            Status::NoResources => f.write_str("No resources"),
+            Status::Timeout => f.write_str("Timeout"),
+            Status::AlreadyConnected => f.write_str("Already connected"),
+            Status::Busy => f.write_str("Busy"),
+            Status::Rejected => f.write_str("Rejected"),
+            Status::NotSupported => f.write_str("Not supported"),
+            Status::InvalidParameters => f.write_str("Invalid parameters"),
+            Status::Disconnected => f.write_str("Disconnected"),
+            Status::NotPowered => f.write_str("Not powered"),
+            Status::Canceled => f.write_str("Canceled"),
+            Status::InvalidIndex => f.write_str("Invalid index"),
+            Status::RFKilled => f.write_str("RF killed"),
+            Status::AlreadyPaired => f.write_str("Already paired"),
+            Status::PermissionDenied => f.write_str("Permission denied"),
+            Status::Other(_) => f.write_str("Unknown status"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// Opening an HCI socket failed.
+    Socket(io::Error),
+    /// Binding an HCI socket to its channel failed, e.g. without the needed capability.
+    Bind(io::Error),
+    /// Reading from or writing to an open socket failed.
+    Io(io::Error),
+    NoResponse,
+    /// The kernel answered the command with a non-zero status.
+    Command {
+        opcode: u16,
+        ctrl_index: u16,
+        status: Status,
+    },
+    /// The controller answered an HCI command with a non-zero status, see `hci`.
+    Hci {
+        opcode: u16,
+        status: u8,
+    },
+    /// No response or event arrived before the deadline given by the caller. A timeout
+    /// reported by the kernel is a `Command` error with `Status::Timeout`.
+    LocalTimeout,
+    /// Parameters were rejected before anything was sent.
+    InvalidParameters,
+    MalformedResponse(String),
+    UnknownError,
 }
+
+impl Error {
+    /// Returns the error for a command answered with `status`, or `None` on success.
+    pub fn from_status(opcode: u16, ctrl_index: u16, status: u8) -> Option<Error> {
+        Status::from_u8(status).map(|status| Error::Command {
+            opcode,
+            ctrl_index,
+            status,
+        })
+    }
+
+    /// Returns the status of a command the kernel answered with a failure.
+    pub fn status(&self) -> Option<Status> {
+        match *self {
+            Error::Command { status, .. } => Some(status),
+            _ => None,
+        }
+    }
+
+    // wraps errno of the last failed libc call
+    pub(crate) fn last_os_error(wrap: fn(io::Error) -> Error) -> Error {
+        wrap(io::Error::last_os_error())
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Error::SocketError => f.write_str("SocketError"),
-            Error::BindError => f.write_str("BindError"),
+            Error::Socket(ref err) => write!(f, "SocketError: {}", err),
+            Error::Bind(ref err) => write!(f, "BindError: {}", err),
+            Error::Io(ref err) => write!(f, "IoError: {}", err),
             Error::NoResponse => f.write_str("NoResponse"),
-            Error::UnknownCommand => f.write_str("UnknownCommand"),
-            Error::NotConnected => f.write_str("NotConnected"),
-            Error::Failed => f.write_str("Failed"),
-            Error::ConnectFailed => f.write_str("ConnectFailed"),
-            Error::AuthenticationFailed => f.write_str("AuthenticationFailed"),
-            Error::NotPaired => f.write_str("NotPaired"),
-            Error::NoResources => f.write_str("NoResources"),
-            Error::Timeout => f.write_str("Timeout"),
-            Error::AlreadyConnected => f.write_str("AlreadyConnected"),
-            Error::Busy => f.write_str("Busy"),
-            Error::Rejected => f.write_str("Rejected"),
-            Error::NotSupported => f.write_str("NotSupported"),
+            Error::Command {
+                opcode,
+                ctrl_index,
+                status,
+            } => write!(
+                f,
+                "opcode 0x{:04x} on index {} failed: {} (0x{:02x})",
+                opcode,
+                ctrl_index,
+                status,
+                status.code()
+            ),
+            Error::Hci { opcode, status } => write!(
+                f,
+                "HCI opcode 0x{:04x} failed with status 0x{:02x}",
+                opcode, status
+            ),
+            Error::LocalTimeout => f.write_str("LocalTimeout"),
             Error::InvalidParameters => f.write_str("InvalidParameters"),
-            Error::Disconnected => f.write_str("Disconnected"),
-            Error::NotPowered => f.write_str("NotPowered"),
-            Error::Canceled => f.write_str("Canceled"),
-            Error::InvalidIndex => f.write_str("InvalidIndex"),
-            Error::RFKilled => f.write_str("RFKilled"),
-            Error::AlreadyPaired => f.write_str("AlreadyPaired"),
-            Error::PermissionDenied => f.write_str("PermissionDenied"),
+            Error::MalformedResponse(ref reason) => write!(f, "MalformedResponse: {}", reason),
             Error::UnknownError => f.write_str("UnknownError"),
         }
     }
 }
 
 impl StdError for Error {
-    fn description(&self) -> &str {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
-            Error::SocketError => "Error opening socket",
-            Error::BindError => "Error binding to a socket",
-            Error::NoResponse => "Response has not been stored",
-            Error::UnknownCommand => "Unknown command",
-            Error::NotConnected => "Not connected",
-            Error::Failed => "Failed",
-            Error::ConnectFailed => "Connect failed",
-            Error::AuthenticationFailed => "Authentication failed",
-            Error::NotPaired => "Not paired",
-            Error::NoResources => "No resources",
-            Error::Timeout => "Timeout",
-            Error::AlreadyConnected => "Already connected",
-            Error::Busy => "Busy",
-            Error::Rejected => "Rejected",
-            Error::NotSupported => "Not supported",
-            Error::InvalidParameters => "Invalid parameters",
-            Error::Disconnected => "Disconnected",
-            Error::NotPowered => "Not powered",
-            Error::Canceled => "Canceled",
-            Error::InvalidIndex => "Invalid index",
-            Error::RFKilled => "RF killed",
-            Error::AlreadyPaired => "Already paired",
-            Error::PermissionDenied => "Permission denied",
-            Error::UnknownError => "Unknown error",
+            Error::Socket(ref err) | Error::Bind(ref err) | Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_round_trip() {
+        assert!(Status::from_u8(0).is_none());
+        for code in 1..=0xff {
+            assert_eq!(code, Status::from_u8(code).unwrap().code());
         }
+
+        let err = Error::from_status(0x0043, 1, 0x08).unwrap();
+        assert_eq!(Some(Status::Timeout), err.status());
+        assert_eq!(
+            "opcode 0x0043 on index 1 failed: Timeout (0x08)",
+            err.to_string()
+        );
     }
 }
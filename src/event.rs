@@ -0,0 +1,479 @@
+use add_device_cmd::AddDeviceAction;
+use address::Address;
+use adv_monitor::AdvMonitorDevice;
+use class_of_device::ClassOfDevice;
+use experimental::ExpFeature;
+use frame::{le16, le32};
+use get_device_flags_cmd::DeviceFlagsInfo;
+use get_phy_config_cmd::Phys;
+use load_conn_params_cmd::ConnectionParameters;
+use read_controller_config_cmd::ConfigOptions;
+use set_local_name_cmd::LocalName;
+
+use {
+    BTMGMT_EVENT_CODE_ADV_MONITOR_ADDED, BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_FOUND,
+    BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_LOST, BTMGMT_EVENT_CODE_ADV_MONITOR_REMOVED,
+    BTMGMT_EVENT_CODE_CLASS_OF_DEV_CHANGED, BTMGMT_EVENT_CODE_DEVICE_ADDED,
+    BTMGMT_EVENT_CODE_DEVICE_CONNECTED, BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED,
+    BTMGMT_EVENT_CODE_DEVICE_FLAGS_CHANGED, BTMGMT_EVENT_CODE_DEVICE_REMOVED,
+    BTMGMT_EVENT_CODE_EXP_FEATURE_CHANGED, BTMGMT_EVENT_CODE_LOCAL_NAME_CHANGED,
+    BTMGMT_EVENT_CODE_NEW_CONFIG_OPTIONS, BTMGMT_EVENT_CODE_NEW_CONN_PARAM,
+    BTMGMT_EVENT_CODE_PHY_CONFIG_CHANGED, BTMGMT_EVENT_CODE_UNCONF_INDEX_ADDED,
+    BTMGMT_EVENT_CODE_UNCONF_INDEX_REMOVED,
+};
+
+const EVENT_HEADER_SIZE: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectReason {
+    Unspecified,
+    ConnectionTimeout,
+    TerminateLocal,
+    TerminateRemote,
+    AuthFailure,
+    LocalHostSuspend,
+    Unknown,
+}
+
+impl DisconnectReason {
+    pub fn from_byte(value: u8) -> DisconnectReason {
+        match value {
+            0x00 => DisconnectReason::Unspecified,
+            0x01 => DisconnectReason::ConnectionTimeout,
+            0x02 => DisconnectReason::TerminateLocal,
+            0x03 => DisconnectReason::TerminateRemote,
+            0x04 => DisconnectReason::AuthFailure,
+            0x05 => DisconnectReason::LocalHostSuspend,
+            _ => DisconnectReason::Unknown,
+        }
+    }
+
+    pub fn to_byte(self) -> u8 {
+        match self {
+            DisconnectReason::Unspecified => 0x00,
+            DisconnectReason::ConnectionTimeout => 0x01,
+            DisconnectReason::TerminateLocal => 0x02,
+            DisconnectReason::TerminateRemote => 0x03,
+            DisconnectReason::AuthFailure => 0x04,
+            DisconnectReason::LocalHostSuspend => 0x05,
+            DisconnectReason::Unknown => 0xff,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    ClassOfDeviceChanged(ClassOfDevice),
+    LocalNameChanged(LocalName),
+    DeviceConnected {
+        address: Address,
+        flags: u32,
+        eir: Vec<u8>,
+    },
+    DeviceDisconnected {
+        address: Address,
+        reason: DisconnectReason,
+    },
+    DeviceAdded {
+        address: Address,
+        action: AddDeviceAction,
+    },
+    DeviceRemoved {
+        address: Address,
+    },
+    NewConnectionParameter {
+        store_hint: bool,
+        params: ConnectionParameters,
+    },
+    UnconfiguredIndexAdded,
+    UnconfiguredIndexRemoved,
+    NewConfigurationOptions(ConfigOptions),
+    PhyConfigurationChanged(Phys),
+    ExpFeatureChanged(ExpFeature),
+    DeviceFlagsChanged(DeviceFlagsInfo),
+    AdvMonitorAdded(u16),
+    AdvMonitorRemoved(u16),
+    AdvMonitorDeviceFound(AdvMonitorDevice),
+    AdvMonitorDeviceLost {
+        monitor_handle: u16,
+        address: Address,
+    },
+    Unknown(Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+pub struct EventPacket {
+    pub event_code: u16,
+    pub controller_index: u16,
+    pub event: Event,
+}
+
+impl EventPacket {
+    pub fn from_bytes(data: &[u8]) -> Option<EventPacket> {
+        if data.len() < EVENT_HEADER_SIZE {
+            return None;
+        }
+
+        let event_code = u16::from(data[0]) | (u16::from(data[1]) << 8);
+        let controller_index = u16::from(data[2]) | (u16::from(data[3]) << 8);
+        let param_length = (u16::from(data[4]) | (u16::from(data[5]) << 8)) as usize;
+
+        if data.len() < EVENT_HEADER_SIZE + param_length {
+            return None;
+        }
+
+        let parameters = &data[EVENT_HEADER_SIZE..EVENT_HEADER_SIZE + param_length];
+
+        let event = match event_code {
+            BTMGMT_EVENT_CODE_CLASS_OF_DEV_CHANGED => {
+                Event::ClassOfDeviceChanged(ClassOfDevice::from_bytes(parameters)?)
+            }
+            BTMGMT_EVENT_CODE_LOCAL_NAME_CHANGED => {
+                Event::LocalNameChanged(LocalName::from_bytes(parameters)?)
+            }
+            BTMGMT_EVENT_CODE_DEVICE_CONNECTED if parameters.len() >= 13 => {
+                let flags = le32(&parameters[7..11]);
+                let eir_length = le16(&parameters[11..13]) as usize;
+                if parameters.len() < 13 + eir_length {
+                    return None;
+                }
+
+                Event::DeviceConnected {
+                    address: parse_address(parameters),
+                    flags,
+                    eir: parameters[13..13 + eir_length].to_vec(),
+                }
+            }
+            BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED if parameters.len() >= 8 => {
+                Event::DeviceDisconnected {
+                    address: parse_address(parameters),
+                    reason: DisconnectReason::from_byte(parameters[7]),
+                }
+            }
+            BTMGMT_EVENT_CODE_DEVICE_ADDED if parameters.len() >= 8 => Event::DeviceAdded {
+                address: parse_address(parameters),
+                action: AddDeviceAction::from_byte(parameters[7]),
+            },
+            BTMGMT_EVENT_CODE_DEVICE_REMOVED if parameters.len() >= 7 => Event::DeviceRemoved {
+                address: parse_address(parameters),
+            },
+            BTMGMT_EVENT_CODE_NEW_CONN_PARAM if parameters.len() >= 16 => {
+                let mut conn_params = parameters[0..7].to_vec();
+                conn_params.extend_from_slice(&parameters[8..16]);
+
+                Event::NewConnectionParameter {
+                    store_hint: parameters[7] != 0,
+                    params: ConnectionParameters::from_bytes(&conn_params)?,
+                }
+            }
+            BTMGMT_EVENT_CODE_UNCONF_INDEX_ADDED => Event::UnconfiguredIndexAdded,
+            BTMGMT_EVENT_CODE_UNCONF_INDEX_REMOVED => Event::UnconfiguredIndexRemoved,
+            BTMGMT_EVENT_CODE_NEW_CONFIG_OPTIONS => {
+                Event::NewConfigurationOptions(ConfigOptions::from_bytes(parameters)?)
+            }
+            BTMGMT_EVENT_CODE_PHY_CONFIG_CHANGED => {
+                Event::PhyConfigurationChanged(Phys::from_bytes(parameters)?)
+            }
+            BTMGMT_EVENT_CODE_EXP_FEATURE_CHANGED => {
+                Event::ExpFeatureChanged(ExpFeature::from_bytes(parameters)?)
+            }
+            BTMGMT_EVENT_CODE_DEVICE_FLAGS_CHANGED => {
+                Event::DeviceFlagsChanged(DeviceFlagsInfo::from_bytes(parameters)?)
+            }
+            BTMGMT_EVENT_CODE_ADV_MONITOR_ADDED if parameters.len() >= 2 => {
+                Event::AdvMonitorAdded(le16(parameters))
+            }
+            BTMGMT_EVENT_CODE_ADV_MONITOR_REMOVED if parameters.len() >= 2 => {
+                Event::AdvMonitorRemoved(le16(parameters))
+            }
+            BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_FOUND => {
+                Event::AdvMonitorDeviceFound(AdvMonitorDevice::from_bytes(parameters)?)
+            }
+            BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_LOST if parameters.len() >= 9 => {
+                Event::AdvMonitorDeviceLost {
+                    monitor_handle: le16(parameters),
+                    address: parse_address(&parameters[2..]),
+                }
+            }
+            BTMGMT_EVENT_CODE_ADV_MONITOR_ADDED
+            | BTMGMT_EVENT_CODE_ADV_MONITOR_REMOVED
+            | BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_LOST
+            | BTMGMT_EVENT_CODE_DEVICE_CONNECTED
+            | BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED
+            | BTMGMT_EVENT_CODE_DEVICE_ADDED
+            | BTMGMT_EVENT_CODE_DEVICE_REMOVED
+            | BTMGMT_EVENT_CODE_NEW_CONN_PARAM => return None,
+            _ => Event::Unknown(parameters.to_vec()),
+        };
+
+        Some(EventPacket {
+            event_code,
+            controller_index,
+            event,
+        })
+    }
+}
+
+// callers must make sure parameters holds at least 7 bytes
+fn parse_address(parameters: &[u8]) -> Address {
+    let mut address: [u8; 6] = Default::default();
+    address.copy_from_slice(&parameters[0..6]);
+
+    Address::from_bytes(address, parameters[6])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use address::AddressType;
+    use class_of_device::MajorDeviceClass;
+    use get_device_flags_cmd::DeviceFlags;
+
+    // frames an event for controller 0
+    fn frame(event_code: u16, params: &[u8]) -> Vec<u8> {
+        let mut data = event_code.to_le_bytes().to_vec();
+        data.extend_from_slice(&[0x00, 0x00]);
+        data.extend_from_slice(&(params.len() as u16).to_le_bytes());
+        data.extend_from_slice(params);
+        data
+    }
+
+    #[test]
+    fn device_added() {
+        let data = [
+            0x1a, 0x00, 0x00, 0x00, 0x08, 0x00, 0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xab, 0x02, 0x01,
+        ];
+        let packet = EventPacket::from_bytes(&data).unwrap();
+        assert_eq!(BTMGMT_EVENT_CODE_DEVICE_ADDED, packet.event_code);
+        match packet.event {
+            Event::DeviceAdded { address, action } => {
+                assert_eq!("AB:BC:CD:DE:EF:F1", address.to_string());
+                assert_eq!(AddressType::LeRandom, address.address_type);
+                assert_eq!(AddDeviceAction::AllowIncoming, action);
+            }
+            _ => panic!("unexpected event"),
+        }
+    }
+
+    #[test]
+    fn truncated_device_removed() {
+        let data = [0x1b, 0x00, 0x00, 0x00, 0x07, 0x00, 0xf1, 0xef, 0xde];
+        assert!(EventPacket::from_bytes(&data).is_none());
+    }
+
+    #[test]
+    fn device_flags_changed() {
+        let data = [
+            0x2a, 0x00, 0x00, 0x00, 0x0f, 0x00, 0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xab, 0x01, 0x03,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        ];
+        match EventPacket::from_bytes(&data).unwrap().event {
+            Event::DeviceFlagsChanged(info) => {
+                assert_eq!(AddressType::LePublic, info.address.address_type);
+                assert!(info
+                    .supported
+                    .contains(DeviceFlags::REMOTE_WAKEUP | DeviceFlags::DEVICE_PRIVACY_MODE));
+                assert_eq!(DeviceFlags::REMOTE_WAKEUP, info.current);
+            }
+            _ => panic!("unexpected event"),
+        }
+    }
+
+    #[test]
+    fn new_conn_param() {
+        let params = [
+            0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xab, 0x02, 0x01, 0x18, 0x00, 0x28, 0x00, 0x01, 0x00,
+            0xf4, 0x01,
+        ];
+        match EventPacket::from_bytes(&frame(BTMGMT_EVENT_CODE_NEW_CONN_PARAM, &params))
+            .unwrap()
+            .event
+        {
+            Event::NewConnectionParameter { store_hint, params } => {
+                assert!(store_hint);
+                assert_eq!("AB:BC:CD:DE:EF:F1", params.address.to_string());
+                assert_eq!(AddressType::LeRandom, params.address.address_type);
+                assert_eq!(0x0018, params.min_interval);
+                assert_eq!(0x0028, params.max_interval);
+                assert_eq!(0x0001, params.latency);
+                assert_eq!(0x01f4, params.supervision_timeout);
+            }
+            _ => panic!("unexpected event"),
+        }
+
+        let data = frame(BTMGMT_EVENT_CODE_NEW_CONN_PARAM, &params[0..15]);
+        assert!(EventPacket::from_bytes(&data).is_none());
+    }
+
+    #[test]
+    fn disconnect_reason() {
+        for value in 0x00..=0x05 {
+            assert_eq!(value, DisconnectReason::from_byte(value).to_byte());
+        }
+        assert_eq!(
+            DisconnectReason::LocalHostSuspend,
+            DisconnectReason::from_byte(0x05)
+        );
+        assert_eq!(DisconnectReason::Unknown, DisconnectReason::from_byte(0x06));
+        assert_eq!(DisconnectReason::Unknown, DisconnectReason::from_byte(0xff));
+    }
+
+    #[test]
+    fn local_name_changed() {
+        let mut params = vec![0u8; 249 + 11];
+        params[0..4].copy_from_slice(b"hci0");
+        params[249..252].copy_from_slice(b"hc0");
+        match EventPacket::from_bytes(&frame(BTMGMT_EVENT_CODE_LOCAL_NAME_CHANGED, &params))
+            .unwrap()
+            .event
+        {
+            Event::LocalNameChanged(name) => {
+                assert_eq!("hci0", name.name);
+                assert_eq!("hc0", name.short_name);
+            }
+            _ => panic!("unexpected event"),
+        }
+
+        let data = frame(BTMGMT_EVENT_CODE_LOCAL_NAME_CHANGED, &params[0..259]);
+        assert!(EventPacket::from_bytes(&data).is_none());
+    }
+
+    #[test]
+    fn class_of_device_changed() {
+        let params = [0x0c, 0x01, 0x02];
+        match EventPacket::from_bytes(&frame(BTMGMT_EVENT_CODE_CLASS_OF_DEV_CHANGED, &params))
+            .unwrap()
+            .event
+        {
+            Event::ClassOfDeviceChanged(class) => {
+                assert_eq!(MajorDeviceClass::Computer, class.major);
+                assert_eq!(0x03, class.minor);
+                assert_eq!(0x02, class.service_classes);
+            }
+            _ => panic!("unexpected event"),
+        }
+
+        let data = frame(BTMGMT_EVENT_CODE_CLASS_OF_DEV_CHANGED, &params[0..2]);
+        assert!(EventPacket::from_bytes(&data).is_none());
+    }
+
+    #[test]
+    fn unconf_index() {
+        let data = frame(BTMGMT_EVENT_CODE_UNCONF_INDEX_ADDED, &[]);
+        let packet = EventPacket::from_bytes(&data).unwrap();
+        assert_eq!(0, packet.controller_index);
+        match packet.event {
+            Event::UnconfiguredIndexAdded => {}
+            _ => panic!("unexpected event"),
+        }
+        assert!(EventPacket::from_bytes(&data[0..5]).is_none());
+
+        let data = frame(BTMGMT_EVENT_CODE_UNCONF_INDEX_REMOVED, &[]);
+        match EventPacket::from_bytes(&data).unwrap().event {
+            Event::UnconfiguredIndexRemoved => {}
+            _ => panic!("unexpected event"),
+        }
+        assert!(EventPacket::from_bytes(&data[0..5]).is_none());
+    }
+
+    #[test]
+    fn new_config_options() {
+        let params = [0x02, 0x00, 0x00, 0x00];
+        match EventPacket::from_bytes(&frame(BTMGMT_EVENT_CODE_NEW_CONFIG_OPTIONS, &params))
+            .unwrap()
+            .event
+        {
+            Event::NewConfigurationOptions(options) => {
+                assert_eq!(ConfigOptions::PUBLIC_ADDRESS, options);
+            }
+            _ => panic!("unexpected event"),
+        }
+
+        let data = frame(BTMGMT_EVENT_CODE_NEW_CONFIG_OPTIONS, &params[0..3]);
+        assert!(EventPacket::from_bytes(&data).is_none());
+    }
+
+    #[test]
+    fn phy_configuration_changed() {
+        let params = [0x00, 0x06, 0x00, 0x00];
+        match EventPacket::from_bytes(&frame(BTMGMT_EVENT_CODE_PHY_CONFIG_CHANGED, &params))
+            .unwrap()
+            .event
+        {
+            Event::PhyConfigurationChanged(phys) => {
+                assert_eq!(Phys::LE_1M, phys);
+            }
+            _ => panic!("unexpected event"),
+        }
+
+        let data = frame(BTMGMT_EVENT_CODE_PHY_CONFIG_CHANGED, &params[0..3]);
+        assert!(EventPacket::from_bytes(&data).is_none());
+    }
+
+    #[test]
+    fn adv_monitor_added_removed() {
+        let data = frame(BTMGMT_EVENT_CODE_ADV_MONITOR_ADDED, &[0x01, 0x02]);
+        match EventPacket::from_bytes(&data).unwrap().event {
+            Event::AdvMonitorAdded(handle) => assert_eq!(0x0201, handle),
+            _ => panic!("unexpected event"),
+        }
+        let data = frame(BTMGMT_EVENT_CODE_ADV_MONITOR_ADDED, &[0x01]);
+        assert!(EventPacket::from_bytes(&data).is_none());
+
+        let data = frame(BTMGMT_EVENT_CODE_ADV_MONITOR_REMOVED, &[0x01, 0x02]);
+        match EventPacket::from_bytes(&data).unwrap().event {
+            Event::AdvMonitorRemoved(handle) => assert_eq!(0x0201, handle),
+            _ => panic!("unexpected event"),
+        }
+        let data = frame(BTMGMT_EVENT_CODE_ADV_MONITOR_REMOVED, &[0x01]);
+        assert!(EventPacket::from_bytes(&data).is_none());
+    }
+
+    #[test]
+    fn adv_monitor_device_found() {
+        let params = [
+            0x01, 0x00, 0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xab, 0x02, 0xc4, 0x04, 0x00, 0x00, 0x00,
+            0x03, 0x00, 0x02, 0x01, 0x06,
+        ];
+        match EventPacket::from_bytes(&frame(BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_FOUND, &params))
+            .unwrap()
+            .event
+        {
+            Event::AdvMonitorDeviceFound(device) => {
+                assert_eq!(1, device.monitor_handle);
+                assert_eq!("AB:BC:CD:DE:EF:F1", device.address.to_string());
+                assert_eq!(AddressType::LeRandom, device.address.address_type);
+                assert_eq!(-60, device.rssi);
+                assert_eq!(0x04, device.flags);
+                assert_eq!(vec![0x02, 0x01, 0x06], device.ad_data);
+            }
+            _ => panic!("unexpected event"),
+        }
+
+        // the AD data is shorter than announced
+        let data = frame(BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_FOUND, &params[0..18]);
+        assert!(EventPacket::from_bytes(&data).is_none());
+    }
+
+    #[test]
+    fn adv_monitor_device_lost() {
+        let params = [0x01, 0x00, 0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xab, 0x01];
+        match EventPacket::from_bytes(&frame(BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_LOST, &params))
+            .unwrap()
+            .event
+        {
+            Event::AdvMonitorDeviceLost {
+                monitor_handle,
+                address,
+            } => {
+                assert_eq!(1, monitor_handle);
+                assert_eq!("AB:BC:CD:DE:EF:F1", address.to_string());
+                assert_eq!(AddressType::LePublic, address.address_type);
+            }
+            _ => panic!("unexpected event"),
+        }
+
+        let data = frame(BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_LOST, &params[0..8]);
+        assert!(EventPacket::from_bytes(&data).is_none());
+    }
+}
@@ -0,0 +1,105 @@
+use frame::le32;
+use uuid::Uuid;
+
+// flags of an experimental feature
+pub const EXP_FEATURE_ENABLED: u32 = 1 << 0;
+pub const EXP_FEATURE_SETTINGS_CHANGED: u32 = 1 << 1;
+
+// known features; DEBUG and ISO_SOCKET are global and set on the non-controller index
+// 0xffff, the others are set per controller
+
+// d4992530-b9ec-469f-ab01-6c481c47da1c
+pub const DEBUG: Uuid = Uuid {
+    bytes: [
+        0xd4, 0x99, 0x25, 0x30, 0xb9, 0xec, 0x46, 0x9f, 0xab, 0x01, 0x6c, 0x48, 0x1c, 0x47, 0xda,
+        0x1c,
+    ],
+};
+// 671b10b5-42c0-4696-9227-eb28d1b049d6
+pub const SIMULTANEOUS_CENTRAL_PERIPHERAL: Uuid = Uuid {
+    bytes: [
+        0x67, 0x1b, 0x10, 0xb5, 0x42, 0xc0, 0x46, 0x96, 0x92, 0x27, 0xeb, 0x28, 0xd1, 0xb0, 0x49,
+        0xd6,
+    ],
+};
+// 15c0a148-c273-11ea-b3de-0242ac130004
+pub const LL_PRIVACY: Uuid = Uuid {
+    bytes: [
+        0x15, 0xc0, 0xa1, 0x48, 0xc2, 0x73, 0x11, 0xea, 0xb3, 0xde, 0x02, 0x42, 0xac, 0x13, 0x00,
+        0x04,
+    ],
+};
+// 330859bc-7506-492d-9370-9a6f0614037f
+pub const QUALITY_REPORT: Uuid = Uuid {
+    bytes: [
+        0x33, 0x08, 0x59, 0xbc, 0x75, 0x06, 0x49, 0x2d, 0x93, 0x70, 0x9a, 0x6f, 0x06, 0x14, 0x03,
+        0x7f,
+    ],
+};
+// a6695ace-ee7f-4fb9-881a-5fac66c629af
+pub const OFFLOAD_CODECS: Uuid = Uuid {
+    bytes: [
+        0xa6, 0x69, 0x5a, 0xce, 0xee, 0x7f, 0x4f, 0xb9, 0x88, 0x1a, 0x5f, 0xac, 0x66, 0xc6, 0x29,
+        0xaf,
+    ],
+};
+// 6fbaf188-05e0-496a-9885-d6ddfdb4e03e
+pub const ISO_SOCKET: Uuid = Uuid {
+    bytes: [
+        0x6f, 0xba, 0xf1, 0x88, 0x05, 0xe0, 0x49, 0x6a, 0x98, 0x85, 0xd6, 0xdd, 0xfd, 0xb4, 0xe0,
+        0x3e,
+    ],
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpFeature {
+    pub uuid: Uuid,
+    pub flags: u32,
+}
+
+impl ExpFeature {
+    pub fn from_bytes(data: &[u8]) -> Option<ExpFeature> {
+        if data.len() < 20 {
+            return None;
+        }
+
+        Some(ExpFeature {
+            uuid: Uuid::from_le_bytes(&data[0..16])?,
+            flags: le32(&data[16..20]),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.flags & EXP_FEATURE_ENABLED != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use event::{Event, EventPacket};
+    use BTMGMT_EVENT_CODE_EXP_FEATURE_CHANGED;
+
+    #[test]
+    fn feature_changed_event() {
+        let mut frame = vec![0x27, 0x00, 0x00, 0x00, 0x14, 0x00];
+        frame.extend_from_slice(&LL_PRIVACY.to_le_bytes());
+        frame.extend_from_slice(&[0x03, 0x00, 0x00, 0x00]);
+
+        let packet = EventPacket::from_bytes(&frame).unwrap();
+        assert_eq!(BTMGMT_EVENT_CODE_EXP_FEATURE_CHANGED, packet.event_code);
+        match packet.event {
+            Event::ExpFeatureChanged(feature) => {
+                assert_eq!(LL_PRIVACY, feature.uuid);
+                assert!(feature.is_enabled());
+                assert_eq!(
+                    EXP_FEATURE_SETTINGS_CHANGED,
+                    feature.flags & !EXP_FEATURE_ENABLED
+                );
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        assert!(ExpFeature::from_bytes(&frame[6..25]).is_none());
+    }
+}
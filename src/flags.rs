@@ -0,0 +1,83 @@
+/// Defines a newtype over a bitmask of the mgmt API, with one associated constant per flag,
+/// `from_bytes` decoding it little-endian, `contains`, `is_empty` and the `|`, `&` and `!`
+/// operators:
+///
+/// ```ignore
+/// flags! {
+///     pub struct ConfigOptions(u32) {
+///         const EXTERNAL_CONFIG = 1 << 0;
+///         const PUBLIC_ADDRESS = 1 << 1;
+///     }
+/// }
+/// ```
+///
+/// Bits without a constant are kept, so a value read from the kernel round-trips.
+macro_rules! flags {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident($bits:ty) {
+            $(
+                $(#[$flag_attr:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        pub struct $name(pub $bits);
+
+        impl $name {
+            $(
+                $(#[$flag_attr])*
+                pub const $flag: $name = $name($value);
+            )*
+
+            /// Decodes the bitmask from the start of `data`, `None` if it is too short.
+            pub fn from_bytes(data: &[u8]) -> Option<$name> {
+                let size = ::std::mem::size_of::<$bits>();
+                if data.len() < size {
+                    return None;
+                }
+
+                let mut bits: $bits = 0;
+                for (i, byte) in data[0..size].iter().enumerate() {
+                    bits |= <$bits>::from(*byte) << (8 * i);
+                }
+
+                Some($name(bits))
+            }
+
+            pub fn contains(self, other: $name) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            pub fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+        }
+
+        impl ::std::ops::BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, other: $name) -> $name {
+                $name(self.0 | other.0)
+            }
+        }
+
+        impl ::std::ops::BitAnd for $name {
+            type Output = $name;
+
+            fn bitand(self, other: $name) -> $name {
+                $name(self.0 & other.0)
+            }
+        }
+
+        impl ::std::ops::Not for $name {
+            type Output = $name;
+
+            fn not(self) -> $name {
+                $name(!self.0)
+            }
+        }
+    };
+}
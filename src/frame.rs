@@ -0,0 +1,182 @@
+use address::Address;
+use error::Error;
+
+const HEADER_SIZE: usize = 6;
+
+/// Validates a Command Complete or Command Status frame and returns a reader over its
+/// return parameters. A non-zero status is turned into the matching error.
+pub fn response_params(response: &[u8]) -> Result<Params<'_>, Error> {
+    let (opcode, status, data) = split_response(response)?;
+    let ctrl_index = u16::from(response[2]) | (u16::from(response[3]) << 8);
+    if let Some(err) = Error::from_status(opcode, ctrl_index, status) {
+        return Err(err);
+    }
+
+    Ok(Params {
+        opcode,
+        data,
+        offset: 0,
+    })
+}
+
+/// Splits a Command Complete or Command Status frame into opcode, status and return
+/// parameters. The parameter length announced in the header is checked against the bytes
+/// actually read.
+pub fn split_response(response: &[u8]) -> Result<(u16, u8, &[u8]), Error> {
+    if response.is_empty() {
+        return Err(Error::NoResponse);
+    }
+
+    if response.len() < HEADER_SIZE {
+        return Err(Error::MalformedResponse(format!(
+            "{} bytes read, shorter than the {} byte header",
+            response.len(),
+            HEADER_SIZE
+        )));
+    }
+
+    let param_length = (u16::from(response[4]) | (u16::from(response[5]) << 8)) as usize;
+    if response.len() < HEADER_SIZE + param_length {
+        return Err(Error::MalformedResponse(format!(
+            "header announces {} parameter bytes, {} read",
+            param_length,
+            response.len() - HEADER_SIZE
+        )));
+    }
+
+    if param_length < 3 {
+        return Err(Error::MalformedResponse(format!(
+            "{} parameter bytes, missing opcode and status",
+            param_length
+        )));
+    }
+
+    Ok((
+        u16::from(response[6]) | (u16::from(response[7]) << 8),
+        response[8],
+        &response[HEADER_SIZE + 3..HEADER_SIZE + param_length],
+    ))
+}
+
+/// Reads a little-endian u16 from the start of `data`, which the caller checked to be long
+/// enough. For decoders working on event parameters rather than `Params`.
+pub fn le16(data: &[u8]) -> u16 {
+    u16::from(data[0]) | (u16::from(data[1]) << 8)
+}
+
+/// Reads a little-endian u32 from the start of `data`, see `le16`.
+pub fn le32(data: &[u8]) -> u32 {
+    u32::from(data[0])
+        | (u32::from(data[1]) << 8)
+        | (u32::from(data[2]) << 16)
+        | (u32::from(data[3]) << 24)
+}
+
+/// A cursor over the return parameters of a command, failing with
+/// `Error::MalformedResponse` instead of reading past their end.
+pub struct Params<'a> {
+    opcode: u16,
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Params<'a> {
+    pub fn bytes(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() - self.offset < length {
+            return Err(self.malformed(&format!("{} bytes needed", length)));
+        }
+
+        let bytes = &self.data[self.offset..self.offset + length];
+        self.offset += length;
+
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, Error> {
+        Ok(le16(self.bytes(2)?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        Ok(le32(self.bytes(4)?))
+    }
+
+    pub fn address(&mut self) -> Result<Address, Error> {
+        let b = self.bytes(7)?;
+        let mut address: [u8; 6] = Default::default();
+        address.copy_from_slice(&b[0..6]);
+
+        Ok(Address::from_bytes(address, b[6]))
+    }
+
+    /// The parameters not read yet.
+    pub fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.offset..];
+        self.offset = self.data.len();
+
+        rest
+    }
+
+    /// Decodes the remaining parameters with one of the `from_bytes` functions.
+    pub fn decode<T, F>(&mut self, what: &str, from_bytes: F) -> Result<T, Error>
+    where
+        F: FnOnce(&[u8]) -> Option<T>,
+    {
+        let offset = self.offset;
+        match from_bytes(self.rest()) {
+            Some(value) => Ok(value),
+            None => {
+                self.offset = offset;
+                Err(self.malformed(&format!("invalid {}", what)))
+            }
+        }
+    }
+
+    fn malformed(&self, reason: &str) -> Error {
+        Error::MalformedResponse(format!(
+            "opcode 0x{:04x}: {} at offset {} of {} parameter bytes",
+            self.opcode,
+            reason,
+            self.offset,
+            self.data.len()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncated_frames() {
+        assert!(matches!(response_params(&[]), Err(Error::NoResponse)));
+        assert!(matches!(
+            response_params(&[0x01, 0x00, 0x00]),
+            Err(Error::MalformedResponse(_))
+        ));
+
+        // header announces 10 parameter bytes, only 4 follow
+        let short = [0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x13, 0x00, 0x00, 0xf1];
+        assert!(matches!(
+            response_params(&short),
+            Err(Error::MalformedResponse(_))
+        ));
+    }
+
+    #[test]
+    fn reads_within_parameters() {
+        // trailing bytes beyond the announced length are ignored
+        let frame = [
+            0x01, 0x00, 0x00, 0x00, 0x05, 0x00, 0x13, 0x00, 0x00, 0x34, 0x12, 0xff,
+        ];
+        let mut params = response_params(&frame).unwrap();
+        assert_eq!(0x1234, params.u16().unwrap());
+        assert!(match params.u8() {
+            Err(Error::MalformedResponse(reason)) => reason.starts_with("opcode 0x0013"),
+            _ => false,
+        });
+    }
+}
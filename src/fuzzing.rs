@@ -0,0 +1,234 @@
+//! Entry points for the fuzz targets in `fuzz/`, which feed arbitrary bytes to every
+//! response parser and event decoder. None of them may panic, whatever the input.
+
+use add_adv_patterns_monitor_cmd::AddAdvPatternsMonitorCommand;
+use add_device_cmd::{AddDeviceAction, AddDeviceCommand};
+use add_remote_oob_data_cmd::AddRemoteOobDataCommand;
+use add_uuid_cmd::AddUuidCommand;
+use address::{Address, AddressType};
+use adv_monitor::AdvMonitor;
+use class_of_device::{ClassOfDevice, MajorDeviceClass};
+use cmd;
+use def_config_cmd::{
+    ReadDefConfigCommand, SetDefConfigCommand, READ_DEF_RUNTIME_CONFIG_OPCODE,
+    READ_DEF_SYSTEM_CONFIG_OPCODE, SET_DEF_SYSTEM_CONFIG_OPCODE,
+};
+use disconnect_cmd::DisconnectCommand;
+use event::EventPacket;
+use get_clock_info_cmd::GetClockInfoCommand;
+use get_connection_info_cmd::GetConnectionInfoCommand;
+use get_connections_cmd::GetConnectionsCommand;
+use get_device_flags_cmd::{DeviceFlags, GetDeviceFlagsCommand};
+use get_phy_config_cmd::{GetPhyConfigCommand, Phys};
+use get_supported_cmds_cmd::GetSupportedCmdsCommand;
+use hci::HciEvent;
+use irk::Irk;
+use load_conn_params_cmd::LoadConnParamsCommand;
+use monitor::MonitorRecord;
+use oob::{EirIterator, OobData, OobValues};
+use read_adv_monitor_features_cmd::ReadAdvMonitorFeaturesCommand;
+use read_controller_cap_cmd::ReadControllerCapCommand;
+use read_controller_config_cmd::ReadControllerConfigCommand;
+use read_controller_info_cmd::ReadControllerInfoCommand;
+use read_exp_features_cmd::ReadExpFeaturesCommand;
+use read_local_oob_data_cmd::ReadLocalOobDataCommand;
+use read_local_oob_ext_data_cmd::ReadLocalOobExtDataCommand;
+use read_unconf_index_list_cmd::ReadUnconfIndexListCommand;
+use remove_adv_monitor_cmd::RemoveAdvMonitorCommand;
+use remove_device_cmd::RemoveDeviceCommand;
+use remove_remote_oob_data_cmd::RemoveRemoteOobDataCommand;
+use remove_uuid_cmd::RemoveUuidCommand;
+use set_appearance_cmd::SetAppearanceCommand;
+use set_debug_keys_cmd::{DebugKeysMode, SetDebugKeysCommand};
+use set_dev_class_cmd::SetDevClassCommand;
+use set_device_flags_cmd::SetDeviceFlagsCommand;
+use set_exp_feature_cmd::SetExpFeatureCommand;
+use set_external_config_cmd::SetExternalConfigCommand;
+use set_local_name_cmd::SetLocalNameCommand;
+use set_phy_config_cmd::SetPhyConfigCommand;
+use set_privacy_cmd::{PrivacyMode, SetPrivacyCommand};
+use set_public_address_cmd::SetPublicAddressCommand;
+use set_scan_params_cmd::SetScanParamsCommand;
+use set_secure_conn_cmd::{SecureConnectionsMode, SetSecureConnCommand};
+use set_static_address_cmd::SetStaticAddressCommand;
+use tlv::TlvList;
+use unpair_device_cmd::UnpairDeviceCommand;
+use uuid::Uuid;
+
+use std::time;
+
+// matches the frame the way `BTMgmt` does, then decodes it regardless of the match
+macro_rules! parse {
+    ($data:expr, $cmd:expr) => {{
+        let cmd = $cmd;
+        cmd::is_response(&cmd, $data);
+        let _ = cmd::decode_response(&cmd, $data);
+    }};
+}
+
+/// Parses `data` as the response to every command.
+pub fn parse_response(data: &[u8]) {
+    let timeout = time::Duration::from_secs(1);
+    let address = Address::from_string("C1:BC:CD:DE:EF:F1", AddressType::LeRandom).unwrap();
+    let uuid = Uuid::from_u16(0x180d);
+    let oob_data = OobData {
+        p192: None,
+        p256: Some(OobValues {
+            hash: [0x11; 16],
+            randomizer: [0x22; 16],
+        }),
+    };
+    let monitor = AdvMonitor::new().pattern(0x03, 0, &[0x0d, 0x18]);
+    let class = ClassOfDevice::new(MajorDeviceClass::Computer, 1).unwrap();
+
+    parse!(
+        data,
+        AddAdvPatternsMonitorCommand::new(0, &monitor, timeout).unwrap()
+    );
+    parse!(
+        data,
+        AddDeviceCommand::new(0, &address, AddDeviceAction::AutoConnect, timeout).unwrap()
+    );
+    parse!(
+        data,
+        AddRemoteOobDataCommand::new(0, &address, &oob_data, timeout).unwrap()
+    );
+    parse!(data, AddUuidCommand::new(0, &uuid, 0, timeout));
+    parse!(data, DisconnectCommand::new(0, &address, timeout));
+    parse!(data, GetClockInfoCommand::new(0, &address, timeout));
+    parse!(data, GetConnectionInfoCommand::new(0, &address, timeout));
+    parse!(data, GetConnectionsCommand::new(0, timeout));
+    parse!(data, GetDeviceFlagsCommand::new(0, &address, timeout));
+    parse!(data, GetPhyConfigCommand::new(0, timeout));
+    parse!(data, GetSupportedCmdsCommand::new(timeout));
+    parse!(data, LoadConnParamsCommand::new(0, &[], timeout).unwrap());
+    parse!(data, ReadAdvMonitorFeaturesCommand::new(0, timeout));
+    parse!(data, ReadControllerCapCommand::new(0, timeout));
+    parse!(data, ReadControllerConfigCommand::new(0, timeout));
+    parse!(data, ReadControllerInfoCommand::new(0, timeout));
+    parse!(
+        data,
+        ReadDefConfigCommand::new(0, READ_DEF_RUNTIME_CONFIG_OPCODE, timeout)
+    );
+    parse!(
+        data,
+        ReadDefConfigCommand::new(0, READ_DEF_SYSTEM_CONFIG_OPCODE, timeout)
+    );
+    parse!(data, ReadExpFeaturesCommand::new(0, timeout));
+    parse!(data, ReadLocalOobDataCommand::new(0, timeout));
+    parse!(data, ReadLocalOobExtDataCommand::new(0, 0x06, timeout));
+    parse!(data, ReadUnconfIndexListCommand::new(timeout));
+    parse!(data, RemoveAdvMonitorCommand::new(0, 0, timeout));
+    parse!(data, RemoveDeviceCommand::new(0, &address, timeout));
+    parse!(data, RemoveRemoteOobDataCommand::new(0, &address, timeout));
+    parse!(data, RemoveUuidCommand::new(0, &uuid, timeout));
+    parse!(data, SetAppearanceCommand::new(0, 0x0340, timeout));
+    parse!(
+        data,
+        SetDebugKeysCommand::new(0, DebugKeysMode::Discard, timeout)
+    );
+    parse!(
+        data,
+        SetDefConfigCommand::new(0, SET_DEF_SYSTEM_CONFIG_OPCODE, &TlvList::new(), timeout)
+            .unwrap()
+    );
+    parse!(data, SetDevClassCommand::new(0, &class, timeout));
+    parse!(
+        data,
+        SetDeviceFlagsCommand::new(0, &address, DeviceFlags::REMOTE_WAKEUP, timeout)
+    );
+    parse!(data, SetExpFeatureCommand::new(0, &uuid, true, timeout));
+    parse!(data, SetExternalConfigCommand::new(0, true, timeout));
+    parse!(
+        data,
+        SetLocalNameCommand::new(0, "fuzz", "", timeout).unwrap()
+    );
+    parse!(data, SetPhyConfigCommand::new(0, Phys::LE_1M, timeout));
+    parse!(
+        data,
+        SetPrivacyCommand::new(0, PrivacyMode::On, &Irk { key: [0; 16] }, timeout)
+    );
+    parse!(data, SetPublicAddressCommand::new(0, &address, timeout));
+    parse!(data, SetScanParamsCommand::new(0, 0x0060, 0x0030, timeout));
+    parse!(
+        data,
+        SetSecureConnCommand::new(0, SecureConnectionsMode::On, timeout)
+    );
+    parse!(
+        data,
+        SetStaticAddressCommand::new(0, &address, timeout).unwrap()
+    );
+    parse!(data, UnpairDeviceCommand::new(0, &address, timeout));
+}
+
+/// Decodes `data` as an event frame.
+pub fn parse_event(data: &[u8]) {
+    let _ = EventPacket::from_bytes(data);
+}
+
+/// Decodes `data` as a frame read from the HCI monitor channel and as an HCI event.
+pub fn parse_monitor(data: &[u8]) {
+    let _ = MonitorRecord::from_bytes(data, time::UNIX_EPOCH);
+    let _ = HciEvent::from_bytes(data);
+}
+
+/// Decodes `data` as EIR/AD structures, e.g. an OOB blob, and as TLV lists.
+pub fn parse_eir(data: &[u8]) {
+    let _ = EirIterator::new(data).count();
+    let _ = OobData::from_eir(data);
+    let _ = OobData::from_bytes(data);
+    let _ = TlvList::from_bytes(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::path::Path;
+
+    // runs every seed, every truncation of it and every single byte flip through `f`
+    fn replay(target: &str, f: fn(&[u8])) {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fuzz/corpus")
+            .join(target);
+        let mut seeds = 0;
+
+        for entry in fs::read_dir(dir).unwrap() {
+            let mut data = fs::read(entry.unwrap().path()).unwrap();
+            for length in 0..=data.len() {
+                f(&data[0..length]);
+            }
+
+            for i in 0..data.len() {
+                data[i] ^= 0xff;
+                f(&data);
+                data[i] ^= 0xff;
+            }
+
+            seeds += 1;
+        }
+
+        assert!(seeds > 0);
+    }
+
+    #[test]
+    fn response_corpus() {
+        replay("response", parse_response);
+    }
+
+    #[test]
+    fn event_corpus() {
+        replay("event", parse_event);
+    }
+
+    #[test]
+    fn eir_corpus() {
+        replay("eir", parse_eir);
+    }
+
+    #[test]
+    fn monitor_corpus() {
+        replay("monitor", parse_monitor);
+    }
+}
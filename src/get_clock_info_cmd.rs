@@ -0,0 +1,72 @@
+use address::Address;
+
+use std::time;
+
+pub const GET_CLOCK_INFO_OPCODE: u16 = 0x0032;
+
+command! {
+    pub struct GetClockInfoCommand(GET_CLOCK_INFO_OPCODE, address) -> ClockInfo {
+        |params| {
+            Ok(ClockInfo {
+                address: params.address()?,
+                local_clock: params.u32()?,
+                piconet_clock: params.u32()?,
+                accuracy: params.u16()?,
+            })
+        }
+    }
+}
+
+impl GetClockInfoCommand {
+    pub fn new(ctrl_index: u16, address: &Address, timeout: time::Duration) -> GetClockInfoCommand {
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&address.address);
+        params.push(address.address_type.to_param());
+
+        GetClockInfoCommand::request(ctrl_index, address, params, timeout)
+    }
+}
+
+/// Bluetooth clock values in units of 312.5us. The piconet clock and accuracy are only
+/// meaningful when the request named a connected device; they are zero otherwise.
+#[derive(Debug)]
+pub struct ClockInfo {
+    pub address: Address,
+    pub local_clock: u32,
+    pub piconet_clock: u32,
+    pub accuracy: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use address::AddressType;
+    use cmd;
+    use error::Error;
+    use simulated::command_complete;
+
+    #[test]
+    fn decode() {
+        let a = Address::from_string("AB:BC:CD:DE:EF:F1", AddressType::BrEdr).unwrap();
+        let cmd = GetClockInfoCommand::new(0, &a, time::Duration::from_secs(1));
+        let params = [
+            0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xab, 0x00, 0x78, 0x56, 0x34, 0x12, 0x04, 0x03, 0x02,
+            0x01, 0xe8, 0x03,
+        ];
+
+        let frame = command_complete(GET_CLOCK_INFO_OPCODE, 0, 0, &params);
+        assert!(cmd::is_response(&cmd, &frame));
+        let info = cmd::decode_response(&cmd, &frame).unwrap();
+        assert_eq!(a, info.address);
+        assert_eq!(0x1234_5678, info.local_clock);
+        assert_eq!(0x0102_0304, info.piconet_clock);
+        assert_eq!(1000, info.accuracy);
+
+        let frame = command_complete(GET_CLOCK_INFO_OPCODE, 0, 0, &params[0..16]);
+        assert!(matches!(
+            cmd::decode_response(&cmd, &frame),
+            Err(Error::MalformedResponse(_))
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+use address::Address;
+
+use std::time;
+
+pub const GET_DEVICE_FLAGS_OPCODE: u16 = 0x004F;
+
+flags! {
+    pub struct DeviceFlags(u32) {
+        const REMOTE_WAKEUP = 1 << 0;
+        const DEVICE_PRIVACY_MODE = 1 << 1;
+        const ADDRESS_RESOLUTION = 1 << 2;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceFlagsInfo {
+    pub address: Address,
+    pub supported: DeviceFlags,
+    pub current: DeviceFlags,
+}
+
+impl DeviceFlagsInfo {
+    pub fn from_bytes(data: &[u8]) -> Option<DeviceFlagsInfo> {
+        if data.len() < 15 {
+            return None;
+        }
+
+        let mut address: [u8; 6] = Default::default();
+        address.copy_from_slice(&data[0..6]);
+
+        Some(DeviceFlagsInfo {
+            address: Address::from_bytes(address, data[6]),
+            supported: DeviceFlags::from_bytes(&data[7..11])?,
+            current: DeviceFlags::from_bytes(&data[11..15])?,
+        })
+    }
+}
+
+command! {
+    pub struct GetDeviceFlagsCommand(GET_DEVICE_FLAGS_OPCODE, address) -> DeviceFlagsInfo {
+        |params| params.decode("device flags", DeviceFlagsInfo::from_bytes)
+    }
+}
+
+impl GetDeviceFlagsCommand {
+    pub fn new(
+        ctrl_index: u16,
+        address: &Address,
+        timeout: time::Duration,
+    ) -> GetDeviceFlagsCommand {
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&address.address);
+        params.push(address.address_type.to_param());
+
+        GetDeviceFlagsCommand::request(ctrl_index, address, params, timeout)
+    }
+}
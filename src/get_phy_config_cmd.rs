@@ -0,0 +1,94 @@
+use error::Error;
+
+use std::time;
+
+pub const GET_PHY_CONFIG_OPCODE: u16 = 0x0044;
+
+flags! {
+    pub struct Phys(u32) {
+        const BR_1M_1SLOT = 1 << 0;
+        const BR_1M_3SLOT = 1 << 1;
+        const BR_1M_5SLOT = 1 << 2;
+        const EDR_2M_1SLOT = 1 << 3;
+        const EDR_2M_3SLOT = 1 << 4;
+        const EDR_2M_5SLOT = 1 << 5;
+        const EDR_3M_1SLOT = 1 << 6;
+        const EDR_3M_3SLOT = 1 << 7;
+        const EDR_3M_5SLOT = 1 << 8;
+        const LE_1M_TX = 1 << 9;
+        const LE_1M_RX = 1 << 10;
+        const LE_2M_TX = 1 << 11;
+        const LE_2M_RX = 1 << 12;
+        const LE_CODED_TX = 1 << 13;
+        const LE_CODED_RX = 1 << 14;
+
+        const LE_1M = Phys::LE_1M_TX.0 | Phys::LE_1M_RX.0;
+        const LE_2M = Phys::LE_2M_TX.0 | Phys::LE_2M_RX.0;
+        const LE_CODED = Phys::LE_CODED_TX.0 | Phys::LE_CODED_RX.0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhyConfiguration {
+    pub supported: Phys,
+    pub configurable: Phys,
+    pub selected: Phys,
+}
+
+impl PhyConfiguration {
+    /// Checks a selection the way the kernel does: it has to be a subset of the supported
+    /// PHYs and include every supported PHY which is not configurable.
+    pub fn validate(&self, selected: Phys) -> Result<(), Error> {
+        if selected.is_empty() || !self.supported.contains(selected) {
+            return Err(Error::InvalidParameters);
+        }
+
+        if !selected.contains(self.supported & !self.configurable) {
+            return Err(Error::InvalidParameters);
+        }
+
+        Ok(())
+    }
+}
+
+command! {
+    pub struct GetPhyConfigCommand(GET_PHY_CONFIG_OPCODE) -> PhyConfiguration {
+        |params| {
+            Ok(PhyConfiguration {
+                supported: Phys(params.u32()?),
+                configurable: Phys(params.u32()?),
+                selected: Phys(params.u32()?),
+            })
+        }
+    }
+}
+
+impl GetPhyConfigCommand {
+    pub fn new(ctrl_index: u16, timeout: time::Duration) -> GetPhyConfigCommand {
+        GetPhyConfigCommand::request(ctrl_index, Vec::new(), timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let config = PhyConfiguration {
+            supported: Phys::BR_1M_1SLOT | Phys::LE_1M | Phys::LE_2M,
+            configurable: Phys::LE_2M,
+            selected: Phys::BR_1M_1SLOT | Phys::LE_1M,
+        };
+
+        assert!(config
+            .validate(Phys::BR_1M_1SLOT | Phys::LE_1M | Phys::LE_2M)
+            .is_ok());
+        // not supported
+        assert!(config
+            .validate(Phys::BR_1M_1SLOT | Phys::LE_1M | Phys::LE_CODED)
+            .is_err());
+        // drops a PHY which is not configurable
+        assert!(config.validate(Phys::BR_1M_1SLOT | Phys::LE_2M).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Direct HCI access through the user channel, for vendor commands and controller bring-up.
+//! While the channel is open the kernel hands the controller over exclusively, it must be
+//! powered off and is not available through mgmt.
+
+use error::Error;
+use socket::{self, HCI_CHANNEL_USER};
+
+use std::time;
+
+const HCI_COMMAND_PKT: u8 = 0x01;
+const HCI_EVENT_PKT: u8 = 0x04;
+
+const EVENT_COMMAND_COMPLETE: u8 = 0x0e;
+const EVENT_COMMAND_STATUS: u8 = 0x0f;
+const EVENT_LE_META: u8 = 0x3e;
+
+const EVENT_HEADER_SIZE: usize = 2;
+const MAX_PARAMS_SIZE: usize = 0xff;
+
+/// Builds a command opcode from its group and command field.
+pub fn opcode(ogf: u8, ocf: u16) -> u16 {
+    (u16::from(ogf) << 10) | (ocf & 0x03ff)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HciEvent {
+    CommandComplete {
+        num_packets: u8,
+        opcode: u16,
+        /// Return parameters, starting with the status for nearly all commands.
+        return_params: Vec<u8>,
+    },
+    CommandStatus {
+        status: u8,
+        num_packets: u8,
+        opcode: u16,
+    },
+    LeMeta {
+        subevent: u8,
+        params: Vec<u8>,
+    },
+    Other {
+        code: u8,
+        params: Vec<u8>,
+    },
+}
+
+impl HciEvent {
+    /// Decodes an event packet without the H4 packet type, as carried by
+    /// `monitor::MonitorPacket::Event`. Returns `None` for truncated packets.
+    pub fn from_bytes(data: &[u8]) -> Option<HciEvent> {
+        if data.len() < EVENT_HEADER_SIZE {
+            return None;
+        }
+
+        let code = data[0];
+        let length = data[1] as usize;
+        if data.len() < EVENT_HEADER_SIZE + length {
+            return None;
+        }
+
+        let params = &data[EVENT_HEADER_SIZE..EVENT_HEADER_SIZE + length];
+        let event = match code {
+            EVENT_COMMAND_COMPLETE if params.len() >= 3 => HciEvent::CommandComplete {
+                num_packets: params[0],
+                opcode: u16::from(params[1]) | (u16::from(params[2]) << 8),
+                return_params: params[3..].to_vec(),
+            },
+            EVENT_COMMAND_STATUS if params.len() >= 4 => HciEvent::CommandStatus {
+                status: params[0],
+                num_packets: params[1],
+                opcode: u16::from(params[2]) | (u16::from(params[3]) << 8),
+            },
+            EVENT_LE_META if !params.is_empty() => HciEvent::LeMeta {
+                subevent: params[0],
+                params: params[1..].to_vec(),
+            },
+            EVENT_COMMAND_COMPLETE | EVENT_COMMAND_STATUS | EVENT_LE_META => return None,
+            _ => HciEvent::Other {
+                code,
+                params: params.to_vec(),
+            },
+        };
+
+        Some(event)
+    }
+}
+
+/// Socket bound to `HCI_CHANNEL_USER` of one controller, which needs `CAP_NET_ADMIN`.
+pub struct HciUserChannel {
+    pub fd: i32,
+}
+
+impl HciUserChannel {
+    /// Takes over controller `ctrl_index`. Fails with a `Bind` error while the controller
+    /// is up or another user channel holds it.
+    pub fn open(ctrl_index: u16) -> Result<HciUserChannel, Error> {
+        Ok(HciUserChannel {
+            fd: socket::open(ctrl_index, HCI_CHANNEL_USER)?,
+        })
+    }
+
+    pub fn send_command(&self, opcode: u16, params: &[u8]) -> Result<(), Error> {
+        if params.len() > MAX_PARAMS_SIZE {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut packet = vec![
+            HCI_COMMAND_PKT,
+            (opcode & 0xff) as u8,
+            (opcode >> 8) as u8,
+            params.len() as u8,
+        ];
+        packet.extend_from_slice(params);
+
+        socket::write(self.fd, &packet)
+    }
+
+    /// Waits up to `timeout` for the next packet. Returns `None` when nothing arrived in
+    /// time or the packet was not a decodable event, e.g. ACL data.
+    pub fn read_event(&self, timeout: time::Duration) -> Result<Option<HciEvent>, Error> {
+        let mut buffer = [0u8; 1 + EVENT_HEADER_SIZE + MAX_PARAMS_SIZE];
+        let bytes = socket::read(self.fd, &mut buffer, timeout)?;
+        if bytes == 0 || buffer[0] != HCI_EVENT_PKT {
+            return Ok(None);
+        }
+
+        Ok(HciEvent::from_bytes(&buffer[1..bytes]))
+    }
+
+    /// Sends a command and waits for its Command Complete or Command Status event. Returns
+    /// the return parameters after the status byte, which are empty for commands answered
+    /// with Command Status; their result arrives later as a separate event.
+    pub fn command(
+        &self,
+        opcode: u16,
+        params: &[u8],
+        timeout: time::Duration,
+    ) -> Result<Vec<u8>, Error> {
+        self.send_command(opcode, params)?;
+
+        let start = time::Instant::now();
+        loop {
+            match self.read_event(time::Duration::from_millis(1))? {
+                Some(HciEvent::CommandComplete {
+                    opcode: op,
+                    return_params,
+                    ..
+                }) if op == opcode => {
+                    return match return_params.split_first() {
+                        Some((&0, rest)) => Ok(rest.to_vec()),
+                        Some((&status, _)) => Err(Error::Hci { opcode, status }),
+                        None => Ok(Vec::new()),
+                    };
+                }
+                Some(HciEvent::CommandStatus {
+                    status, opcode: op, ..
+                }) if op == opcode => {
+                    if status != 0 {
+                        return Err(Error::Hci { opcode, status });
+                    }
+
+                    return Ok(Vec::new());
+                }
+                _ => {}
+            }
+
+            if start.elapsed() > timeout {
+                return Err(Error::LocalTimeout);
+            }
+        }
+    }
+}
+
+impl Drop for HciUserChannel {
+    fn drop(&mut self) {
+        socket::close(self.fd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_events() {
+        // Command Complete for HCI_Read_BD_ADDR
+        assert_eq!(
+            Some(HciEvent::CommandComplete {
+                num_packets: 1,
+                opcode: opcode(0x04, 0x0009),
+                return_params: vec![0x00, 0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xc1],
+            }),
+            HciEvent::from_bytes(&[
+                0x0e, 0x0a, 0x01, 0x09, 0x10, 0x00, 0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xc1
+            ])
+        );
+
+        // Command Status for LE_Create_Connection
+        assert_eq!(
+            Some(HciEvent::CommandStatus {
+                status: 0x0c,
+                num_packets: 1,
+                opcode: 0x200d,
+            }),
+            HciEvent::from_bytes(&[0x0f, 0x04, 0x0c, 0x01, 0x0d, 0x20])
+        );
+
+        assert_eq!(
+            Some(HciEvent::LeMeta {
+                subevent: 0x02,
+                params: vec![0x00],
+            }),
+            HciEvent::from_bytes(&[0x3e, 0x02, 0x02, 0x00])
+        );
+        assert_eq!(
+            Some(HciEvent::Other {
+                code: 0x05,
+                params: vec![0x00, 0x40, 0x00, 0x13],
+            }),
+            HciEvent::from_bytes(&[0x05, 0x04, 0x00, 0x40, 0x00, 0x13])
+        );
+
+        assert!(HciEvent::from_bytes(&[0x0e, 0x02, 0x01, 0x09]).is_none());
+        assert!(HciEvent::from_bytes(&[0x0e, 0x03, 0x01, 0x09]).is_none());
+        assert!(HciEvent::from_bytes(&[0x3e, 0x00]).is_none());
+    }
+}
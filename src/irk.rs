@@ -0,0 +1,92 @@
+use error::Error;
+use hex;
+
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+
+/// An Identity Resolving Key, stored most significant byte first as it is usually written.
+/// The mgmt interface transfers keys least significant byte first, see `to_le_bytes`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Irk {
+    pub key: [u8; 16],
+}
+
+impl Irk {
+    /// Generates a random key from the kernel's random number generator.
+    pub fn generate() -> Result<Irk, Error> {
+        let mut key: [u8; 16] = Default::default();
+        File::open("/dev/urandom")?.read_exact(&mut key)?;
+
+        Ok(Irk { key })
+    }
+
+    pub fn from_le_bytes(data: &[u8]) -> Option<Irk> {
+        if data.len() < 16 {
+            return None;
+        }
+
+        let mut key: [u8; 16] = Default::default();
+        key.copy_from_slice(&data[0..16]);
+        key.reverse();
+
+        Some(Irk { key })
+    }
+
+    pub fn to_le_bytes(&self) -> [u8; 16] {
+        let mut key = self.key;
+        key.reverse();
+
+        key
+    }
+
+    pub fn from_string(key: &str) -> Option<Irk> {
+        let decoded = match hex::decode(key.trim_start_matches("0x")) {
+            Ok(decoded) => decoded,
+            Err(_) => return None,
+        };
+
+        if decoded.len() != 16 {
+            return None;
+        }
+
+        let mut key: [u8; 16] = Default::default();
+        key.copy_from_slice(&decoded);
+
+        Some(Irk { key })
+    }
+}
+
+// keep key material out of logs
+impl fmt::Debug for Irk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Irk { .. }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // sample data from the Core specification, Vol 3, Part H, D.7
+    #[test]
+    fn encoding() {
+        let irk = Irk::from_string("ec0234a357c8ad05341010a60a397d9b").unwrap();
+        assert_eq!(
+            [
+                0x9b, 0x7d, 0x39, 0x0a, 0xa6, 0x10, 0x10, 0x34, 0x05, 0xad, 0xc8, 0x57, 0xa3, 0x34,
+                0x02, 0xec
+            ],
+            irk.to_le_bytes()
+        );
+        assert_eq!(Some(irk.clone()), Irk::from_le_bytes(&irk.to_le_bytes()));
+        assert!(Irk::from_string("ec0234a357c8ad05341010a60a397d").is_none());
+    }
+
+    #[test]
+    fn generate() {
+        let a = Irk::generate().unwrap();
+        let b = Irk::generate().unwrap();
+        assert!(a != b);
+    }
+}
@@ -1,142 +1,404 @@
 extern crate hex;
 extern crate libc;
 
+// declared first, the command! and flags! macros are used by the modules below
+#[macro_use]
+mod cmd;
+#[macro_use]
+mod flags;
+
+mod add_adv_patterns_monitor_cmd;
 mod add_device_cmd;
+mod add_remote_oob_data_cmd;
+mod add_uuid_cmd;
 pub mod address;
-mod cmd;
+pub mod adv_monitor;
+pub mod btsnoop;
+pub mod class_of_device;
+mod def_config_cmd;
+mod disconnect_cmd;
 mod error;
+pub mod event;
+pub mod experimental;
+mod frame;
+#[cfg(any(test, feature = "fuzzing"))]
+#[doc(hidden)]
+pub mod fuzzing;
+mod get_clock_info_cmd;
 mod get_connection_info_cmd;
 mod get_connections_cmd;
+mod get_device_flags_cmd;
+mod get_phy_config_cmd;
 mod get_supported_cmds_cmd;
+pub mod hci;
+pub mod irk;
+mod load_conn_params_cmd;
+pub mod monitor;
+pub mod oob;
+mod raw_cmd;
+mod read_adv_monitor_features_cmd;
+mod read_controller_cap_cmd;
+mod read_controller_config_cmd;
+mod read_controller_info_cmd;
+mod read_exp_features_cmd;
+mod read_local_oob_data_cmd;
+mod read_local_oob_ext_data_cmd;
+mod read_unconf_index_list_cmd;
+mod remove_adv_monitor_cmd;
 mod remove_device_cmd;
+mod remove_remote_oob_data_cmd;
+mod remove_uuid_cmd;
+mod rssi_stats;
+mod set_appearance_cmd;
+mod set_debug_keys_cmd;
+mod set_dev_class_cmd;
+mod set_device_flags_cmd;
+mod set_exp_feature_cmd;
+mod set_external_config_cmd;
+mod set_local_name_cmd;
+mod set_phy_config_cmd;
+mod set_privacy_cmd;
+mod set_public_address_cmd;
 mod set_scan_params_cmd;
+mod set_secure_conn_cmd;
+mod set_static_address_cmd;
+pub mod simulated;
+mod socket;
+pub mod tlv;
+pub mod transport;
 mod unpair_device_cmd;
+pub mod uuid;
 
+use add_adv_patterns_monitor_cmd::AddAdvPatternsMonitorCommand;
 use add_device_cmd::AddDeviceCommand;
-use cmd::Command;
-use error::Error;
+use add_remote_oob_data_cmd::AddRemoteOobDataCommand;
+use add_uuid_cmd::AddUuidCommand;
+use adv_monitor::{AdvMonitor, AdvMonitorFeatures};
+use class_of_device::ClassOfDevice;
+use def_config_cmd::{ReadDefConfigCommand, SetDefConfigCommand};
+use disconnect_cmd::DisconnectCommand;
+use experimental::ExpFeature;
+use get_clock_info_cmd::GetClockInfoCommand;
 use get_connection_info_cmd::GetConnectionInfoCommand;
 use get_connections_cmd::GetConnectionsCommand;
+use get_device_flags_cmd::GetDeviceFlagsCommand;
+use get_phy_config_cmd::GetPhyConfigCommand;
 use get_supported_cmds_cmd::{GetSupportedCmdsCommand, SupportedCmdsResult};
+use irk::Irk;
+use load_conn_params_cmd::LoadConnParamsCommand;
+use oob::OobData;
+use raw_cmd::RawCommand;
+use read_adv_monitor_features_cmd::ReadAdvMonitorFeaturesCommand;
+use read_controller_cap_cmd::ReadControllerCapCommand;
+use read_controller_config_cmd::ReadControllerConfigCommand;
+use read_controller_info_cmd::ReadControllerInfoCommand;
+use read_exp_features_cmd::ReadExpFeaturesCommand;
+use read_local_oob_data_cmd::ReadLocalOobDataCommand;
+use read_local_oob_ext_data_cmd::ReadLocalOobExtDataCommand;
+use read_unconf_index_list_cmd::ReadUnconfIndexListCommand;
+use remove_adv_monitor_cmd::RemoveAdvMonitorCommand;
 use remove_device_cmd::RemoveDeviceCommand;
+use remove_remote_oob_data_cmd::RemoveRemoteOobDataCommand;
+use remove_uuid_cmd::RemoveUuidCommand;
+use set_appearance_cmd::SetAppearanceCommand;
+use set_debug_keys_cmd::SetDebugKeysCommand;
+use set_dev_class_cmd::SetDevClassCommand;
+use set_device_flags_cmd::SetDeviceFlagsCommand;
+use set_exp_feature_cmd::SetExpFeatureCommand;
+use set_external_config_cmd::SetExternalConfigCommand;
+use set_local_name_cmd::SetLocalNameCommand;
+use set_phy_config_cmd::SetPhyConfigCommand;
+use set_privacy_cmd::SetPrivacyCommand;
+use set_public_address_cmd::SetPublicAddressCommand;
 use set_scan_params_cmd::SetScanParamsCommand;
+use set_secure_conn_cmd::SetSecureConnCommand;
+use set_static_address_cmd::SetStaticAddressCommand;
+use socket::{HCI_CHANNEL_CONTROL, HCI_DEV_NONE};
 use std::sync::mpsc;
 use std::time;
+use tlv::TlvList;
 use unpair_device_cmd::UnpairDeviceCommand;
+use uuid::Uuid;
+
+pub use add_device_cmd::AddDeviceAction;
+pub use cmd::Command;
+pub use error::{Error, Status};
+pub use event::DisconnectReason;
+pub use frame::Params;
+pub use get_clock_info_cmd::ClockInfo;
+pub use get_connection_info_cmd::ConnectionInfo;
+pub use get_device_flags_cmd::{DeviceFlags, DeviceFlagsInfo};
+pub use get_phy_config_cmd::{PhyConfiguration, Phys};
+pub use load_conn_params_cmd::ConnectionParameters;
+pub use raw_cmd::RawResponse;
+pub use read_controller_cap_cmd::{ControllerCapabilities, SecurityFlags};
+pub use read_controller_config_cmd::{ConfigOptions, ControllerConfigInfo};
+pub use read_controller_info_cmd::{ControllerInfo, Settings};
+pub use read_local_oob_ext_data_cmd::LocalOobExtData;
+pub use rssi_stats::RssiStats;
+pub use set_debug_keys_cmd::DebugKeysMode;
+pub use set_local_name_cmd::LocalName;
+pub use set_privacy_cmd::PrivacyMode;
+pub use set_secure_conn_cmd::SecureConnectionsMode;
 
-use std::mem;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
+use add_adv_patterns_monitor_cmd::{
+    ADD_ADV_PATTERNS_MONITOR_OPCODE, ADD_ADV_PATTERNS_MONITOR_RSSI_OPCODE,
+};
 use add_device_cmd::ADD_DEVICE_OPCODE;
+use add_remote_oob_data_cmd::ADD_REMOTE_OOB_DATA_OPCODE;
+use add_uuid_cmd::ADD_UUID_OPCODE;
+use def_config_cmd::{
+    READ_DEF_RUNTIME_CONFIG_OPCODE, READ_DEF_SYSTEM_CONFIG_OPCODE, SET_DEF_RUNTIME_CONFIG_OPCODE,
+    SET_DEF_SYSTEM_CONFIG_OPCODE,
+};
+use disconnect_cmd::DISCONNECT_OPCODE;
+use get_clock_info_cmd::GET_CLOCK_INFO_OPCODE;
+use get_device_flags_cmd::GET_DEVICE_FLAGS_OPCODE;
+use get_phy_config_cmd::GET_PHY_CONFIG_OPCODE;
+use load_conn_params_cmd::LOAD_CONN_PARAMS_OPCODE;
+use read_adv_monitor_features_cmd::READ_ADV_MONITOR_FEATURES_OPCODE;
+use read_controller_cap_cmd::READ_CONTROLLER_CAP_OPCODE;
+use read_controller_config_cmd::READ_CONTROLLER_CONFIG_OPCODE;
+use read_controller_info_cmd::READ_CONTROLLER_INFO_OPCODE;
+use read_exp_features_cmd::READ_EXP_FEATURES_OPCODE;
+use read_local_oob_data_cmd::READ_LOCAL_OOB_DATA_OPCODE;
+use read_local_oob_ext_data_cmd::READ_LOCAL_OOB_EXT_DATA_OPCODE;
+use read_unconf_index_list_cmd::READ_UNCONF_INDEX_LIST_OPCODE;
+use remove_adv_monitor_cmd::REMOVE_ADV_MONITOR_OPCODE;
 use remove_device_cmd::REMOVE_DEVICE_OPCODE;
+use remove_remote_oob_data_cmd::REMOVE_REMOTE_OOB_DATA_OPCODE;
+use remove_uuid_cmd::REMOVE_UUID_OPCODE;
+use set_appearance_cmd::SET_APPEARANCE_OPCODE;
+use set_debug_keys_cmd::SET_DEBUG_KEYS_OPCODE;
+use set_dev_class_cmd::SET_DEV_CLASS_OPCODE;
+use set_device_flags_cmd::SET_DEVICE_FLAGS_OPCODE;
+use set_exp_feature_cmd::SET_EXP_FEATURE_OPCODE;
+use set_external_config_cmd::SET_EXTERNAL_CONFIG_OPCODE;
+use set_local_name_cmd::SET_LOCAL_NAME_OPCODE;
+use set_phy_config_cmd::SET_PHY_CONFIG_OPCODE;
+use set_privacy_cmd::SET_PRIVACY_OPCODE;
+use set_public_address_cmd::SET_PUBLIC_ADDRESS_OPCODE;
 use set_scan_params_cmd::SET_SCAN_PARAMS_OPCODE;
+use set_secure_conn_cmd::SET_SECURE_CONN_OPCODE;
+use set_static_address_cmd::SET_STATIC_ADDRESS_OPCODE;
 
 const COMMAND_RESPONSE_EVENT: u8 = 0x01;
 const COMMAND_STATUS_EVENT: u8 = 0x02;
 
+// how long the methods of `BTMgmt` wait for a response unless changed by `set_timeout`
+const DEFAULT_TIMEOUT: time::Duration = time::Duration::from_secs(1);
+
+pub const BTMGMT_EVENT_CODE_CLASS_OF_DEV_CHANGED: u16 = 0x0007;
+pub const BTMGMT_EVENT_CODE_LOCAL_NAME_CHANGED: u16 = 0x0008;
 pub const BTMGMT_EVENT_CODE_DEVICE_CONNECTED: u16 = 0x000b;
 pub const BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED: u16 = 0x000c;
-pub const BTMGMT_EVENT_DEVICE_DISCONNECTED_REASON_UNSPECIFIED: u8 = 0x00;
-pub const BTMGMT_EVENT_DEVICE_DISCONNECTED_REASON_CONNECTION_TIMEOUT: u8 = 0x01;
-pub const BTMGMT_EVENT_DEVICE_DISCONNECTED_REASON_TERMINATE_LOCAL: u8 = 0x02;
-pub const BTMGMT_EVENT_DEVICE_DISCONNECTED_REASON_TERMINATE_REMOTE: u8 = 0x03;
-pub const BTMGMT_EVENT_DEVICE_DISCONNECTED_REASON_AUTH_FAILURE: u8 = 0x04;
+pub const BTMGMT_EVENT_CODE_DEVICE_ADDED: u16 = 0x001a;
+pub const BTMGMT_EVENT_CODE_DEVICE_REMOVED: u16 = 0x001b;
+pub const BTMGMT_EVENT_CODE_NEW_CONN_PARAM: u16 = 0x001c;
+pub const BTMGMT_EVENT_CODE_UNCONF_INDEX_ADDED: u16 = 0x001d;
+pub const BTMGMT_EVENT_CODE_UNCONF_INDEX_REMOVED: u16 = 0x001e;
+pub const BTMGMT_EVENT_CODE_NEW_CONFIG_OPTIONS: u16 = 0x001f;
+pub const BTMGMT_EVENT_CODE_PHY_CONFIG_CHANGED: u16 = 0x0026;
+pub const BTMGMT_EVENT_CODE_EXP_FEATURE_CHANGED: u16 = 0x0027;
+pub const BTMGMT_EVENT_CODE_DEVICE_FLAGS_CHANGED: u16 = 0x002a;
+pub const BTMGMT_EVENT_CODE_ADV_MONITOR_ADDED: u16 = 0x002b;
+pub const BTMGMT_EVENT_CODE_ADV_MONITOR_REMOVED: u16 = 0x002c;
+pub const BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_FOUND: u16 = 0x002f;
+pub const BTMGMT_EVENT_CODE_ADV_MONITOR_DEVICE_LOST: u16 = 0x0030;
 
 pub const BTMGMT_CMD_OPCODE_ADD_DEVICE: u16 = ADD_DEVICE_OPCODE;
 pub const BTMGMT_CMD_OPCODE_REMOVE_DEVICE: u16 = REMOVE_DEVICE_OPCODE;
 pub const BTMGMT_CMD_OPCODE_SET_SCAN_PARAMS: u16 = SET_SCAN_PARAMS_OPCODE;
+pub const BTMGMT_CMD_OPCODE_LOAD_CONN_PARAMS: u16 = LOAD_CONN_PARAMS_OPCODE;
+pub const BTMGMT_CMD_OPCODE_DISCONNECT: u16 = DISCONNECT_OPCODE;
+pub const BTMGMT_CMD_OPCODE_GET_CLOCK_INFO: u16 = GET_CLOCK_INFO_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_LOCAL_NAME: u16 = SET_LOCAL_NAME_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_DEV_CLASS: u16 = SET_DEV_CLASS_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_APPEARANCE: u16 = SET_APPEARANCE_OPCODE;
+pub const BTMGMT_CMD_OPCODE_ADD_UUID: u16 = ADD_UUID_OPCODE;
+pub const BTMGMT_CMD_OPCODE_REMOVE_UUID: u16 = REMOVE_UUID_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_UNCONF_INDEX_LIST: u16 = READ_UNCONF_INDEX_LIST_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_CONTROLLER_CONFIG: u16 = READ_CONTROLLER_CONFIG_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_EXTERNAL_CONFIG: u16 = SET_EXTERNAL_CONFIG_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_PUBLIC_ADDRESS: u16 = SET_PUBLIC_ADDRESS_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_STATIC_ADDRESS: u16 = SET_STATIC_ADDRESS_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_LOCAL_OOB_DATA: u16 = READ_LOCAL_OOB_DATA_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_LOCAL_OOB_EXT_DATA: u16 = READ_LOCAL_OOB_EXT_DATA_OPCODE;
+pub const BTMGMT_CMD_OPCODE_ADD_REMOTE_OOB_DATA: u16 = ADD_REMOTE_OOB_DATA_OPCODE;
+pub const BTMGMT_CMD_OPCODE_REMOVE_REMOTE_OOB_DATA: u16 = REMOVE_REMOTE_OOB_DATA_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_SECURE_CONN: u16 = SET_SECURE_CONN_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_DEBUG_KEYS: u16 = SET_DEBUG_KEYS_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_PRIVACY: u16 = SET_PRIVACY_OPCODE;
+pub const BTMGMT_CMD_OPCODE_GET_PHY_CONFIG: u16 = GET_PHY_CONFIG_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_PHY_CONFIG: u16 = SET_PHY_CONFIG_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_DEF_SYSTEM_CONFIG: u16 = READ_DEF_SYSTEM_CONFIG_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_DEF_SYSTEM_CONFIG: u16 = SET_DEF_SYSTEM_CONFIG_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_DEF_RUNTIME_CONFIG: u16 = READ_DEF_RUNTIME_CONFIG_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_DEF_RUNTIME_CONFIG: u16 = SET_DEF_RUNTIME_CONFIG_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_CONTROLLER_CAP: u16 = READ_CONTROLLER_CAP_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_CONTROLLER_INFO: u16 = READ_CONTROLLER_INFO_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_EXP_FEATURES: u16 = READ_EXP_FEATURES_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_EXP_FEATURE: u16 = SET_EXP_FEATURE_OPCODE;
+pub const BTMGMT_CMD_OPCODE_GET_DEVICE_FLAGS: u16 = GET_DEVICE_FLAGS_OPCODE;
+pub const BTMGMT_CMD_OPCODE_SET_DEVICE_FLAGS: u16 = SET_DEVICE_FLAGS_OPCODE;
+pub const BTMGMT_CMD_OPCODE_READ_ADV_MONITOR_FEATURES: u16 = READ_ADV_MONITOR_FEATURES_OPCODE;
+pub const BTMGMT_CMD_OPCODE_ADD_ADV_PATTERNS_MONITOR: u16 = ADD_ADV_PATTERNS_MONITOR_OPCODE;
+pub const BTMGMT_CMD_OPCODE_REMOVE_ADV_MONITOR: u16 = REMOVE_ADV_MONITOR_OPCODE;
+pub const BTMGMT_CMD_OPCODE_ADD_ADV_PATTERNS_MONITOR_RSSI: u16 =
+    ADD_ADV_PATTERNS_MONITOR_RSSI_OPCODE;
 
-const BTPROTO_HCI: i32 = 1;
-const HCI_DEV_NONE: u16 = 0xffff;
-const HCI_CHANNEL_CONTROL: u16 = 3;
-
-#[repr(C)]
-struct SockAddrHci {
-    hci_family: libc::sa_family_t,
-    hci_dev: u16,
-    hci_channel: u16,
+pub struct BtmgmtEventPacketStructure {
+    pub event_code: u16,                     //byte loc 00-01
+    pub controller_index: u16,               //byte loc 02-03
+    pub param_lenght: u16,                   //byte loc 04-05
+    pub device_address: address::Address,    //byte loc 06-11 + 12
+    pub disconnect_reason: DisconnectReason, //byte loc 13
 }
 
-pub struct BtmgmtEventPacketStructure {
-    pub event_code: u16,                  //byte loc 00-01
-    pub controller_index: u16,            //byte loc 02-03
-    pub param_lenght: u16,                //byte loc 04-05
-    pub device_address: address::Address, //byte loc 06-11 + 12
-    pub disconnect_reason: u8,            //byte loc 13
+#[derive(Debug, Clone)]
+pub struct AddedDevice {
+    pub ctrl_index: u16,
+    pub address: address::Address,
+    pub action: AddDeviceAction,
 }
 
 pub struct BTMgmt {
     pub fd: i32,
+    transport: Box<dyn transport::Transport>,
+    added_devices: Mutex<Vec<AddedDevice>>,
+    tracer: Mutex<Option<btsnoop::Tracer>>,
+    timeout: time::Duration,
 }
 
 impl BTMgmt {
     pub fn new() -> Result<BTMgmt, Error> {
-        let btmgmt = BTMgmt {
-            fd: unsafe {
-                libc::socket(
-                    libc::PF_BLUETOOTH,
-                    libc::SOCK_RAW | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
-                    BTPROTO_HCI,
-                )
-            },
-        };
+        let transport = transport::SocketTransport::open()?;
+
+        Ok(BTMgmt {
+            fd: transport.fd,
+            transport: Box::new(transport),
+            added_devices: Mutex::new(Vec::new()),
+            tracer: Mutex::new(None),
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
 
-        if btmgmt.fd < 0 {
-            return Err(Error::SocketError);
+    /// Runs the library over another transport, e.g. a simulated kernel. `fd` is -1 for
+    /// such handles.
+    pub fn with_transport(transport: Box<dyn transport::Transport>) -> BTMgmt {
+        BTMgmt {
+            fd: -1,
+            transport,
+            added_devices: Mutex::new(Vec::new()),
+            tracer: Mutex::new(None),
+            timeout: DEFAULT_TIMEOUT,
         }
+    }
 
-        let addr = SockAddrHci {
-            hci_family: libc::AF_BLUETOOTH as u16,
-            hci_dev: HCI_DEV_NONE,
-            hci_channel: HCI_CHANNEL_CONTROL,
-        };
+    /// Sets how long the methods of `BTMgmt` wait for a response, one second by default.
+    /// Commands passed to `execute` carry their own timeout.
+    pub fn set_timeout(&mut self, timeout: time::Duration) {
+        self.timeout = timeout;
+    }
 
-        if unsafe {
-            let addr_ptr = Box::into_raw(Box::new(addr));
-            let ret_val = libc::bind(
-                btmgmt.fd,
-                addr_ptr as *const libc::sockaddr,
-                std::mem::size_of::<SockAddrHci>() as u32,
-            );
-            Box::from_raw(addr_ptr);
-            ret_val
-        } < 0
-        {
-            return Err(Error::BindError);
+    /// Records every command sent and frame received to `tracer`, or stops recording when
+    /// `None`. See `btsnoop::Tracer::set_enabled` to pause a capture instead.
+    pub fn set_tracer(&self, tracer: Option<btsnoop::Tracer>) {
+        if let Ok(mut current) = self.tracer.lock() {
+            *current = tracer;
         }
-
-        Ok(btmgmt)
     }
 
     pub fn get_connections(&self, ctrl_index: u16) -> Result<Vec<address::Address>, Error> {
-        let mut cmd = GetConnectionsCommand::new(ctrl_index, time::Duration::from_secs(1));
-        self.write_command(&mut cmd)?;
-
-        cmd.result()
+        self.execute(GetConnectionsCommand::new(ctrl_index, self.timeout))
     }
 
     pub fn get_connection_info(
         &self,
         ctrl_index: u16,
         address: &address::Address,
-    ) -> Result<get_connection_info_cmd::ConnectionInfo, Error> {
-        let mut cmd =
-            GetConnectionInfoCommand::new(ctrl_index, &address, time::Duration::from_secs(1));
-        self.write_command(&mut cmd)?;
+    ) -> Result<ConnectionInfo, Error> {
+        self.execute(GetConnectionInfoCommand::new(
+            ctrl_index,
+            address,
+            self.timeout,
+        ))
+    }
+
+    /// Samples the connection info of every address `samples` times, one round every
+    /// `interval`, and returns RSSI statistics in the order of `addresses`. A device that is
+    /// not connected during a round, or whose RSSI is unknown, counts as a missed sample.
+    pub fn sample_rssi(
+        &self,
+        ctrl_index: u16,
+        addresses: &[address::Address],
+        interval: time::Duration,
+        samples: usize,
+        ewma_alpha: f64,
+    ) -> Result<Vec<RssiStats>, Error> {
+        let mut stats: Vec<RssiStats> = addresses
+            .iter()
+            .map(|a| RssiStats::new(a, ewma_alpha))
+            .collect();
+
+        let start = time::Instant::now();
+        for round in 0..samples {
+            let deadline = start + interval * round as u32;
+            let now = time::Instant::now();
+            if deadline > now {
+                std::thread::sleep(deadline - now);
+            }
+
+            for s in stats.iter_mut() {
+                match self.get_connection_info(ctrl_index, &s.address) {
+                    Ok(ConnectionInfo {
+                        rssi: Some(rssi), ..
+                    }) => s.add(rssi),
+                    Ok(_) => s.add_missed(),
+                    Err(ref err) if err.status() == Some(Status::NotConnected) => s.add_missed(),
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        Ok(stats)
+    }
 
-        cmd.result()
+    /// Reads the local clock and, for a connected device, the piconet clock of the
+    /// connection. Passing the all-zero BR/EDR address reads only the local clock.
+    pub fn get_clock_info(
+        &self,
+        ctrl_index: u16,
+        address: &address::Address,
+    ) -> Result<ClockInfo, Error> {
+        self.execute(GetClockInfoCommand::new(ctrl_index, address, self.timeout))
     }
 
     pub fn add_device(
         &self,
         ctrl_index: u16,
         address: &address::Address,
+        action: AddDeviceAction,
     ) -> Result<address::Address, Error> {
-        let mut cmd = AddDeviceCommand::new(ctrl_index, &address, time::Duration::from_secs(1));
-        self.write_command(&mut cmd)?;
+        let added = self.execute(AddDeviceCommand::new(
+            ctrl_index,
+            address,
+            action,
+            self.timeout,
+        )?)?;
+        if let Ok(mut devices) = self.added_devices.lock() {
+            devices.retain(|d| d.ctrl_index != ctrl_index || d.address != added);
+            devices.push(AddedDevice {
+                ctrl_index,
+                address: added.clone(),
+                action,
+            });
+        }
 
-        cmd.result()
+        Ok(added)
     }
 
     pub fn remove_device(
@@ -144,28 +406,68 @@ impl BTMgmt {
         ctrl_index: u16,
         address: &address::Address,
     ) -> Result<address::Address, Error> {
-        let mut cmd = RemoveDeviceCommand::new(ctrl_index, &address, time::Duration::from_secs(1));
-        self.write_command(&mut cmd)?;
+        let removed = self.execute(RemoveDeviceCommand::new(ctrl_index, address, self.timeout))?;
+        if let Ok(mut devices) = self.added_devices.lock() {
+            // the all-zero address removes every device added to the controller
+            let remove_all = removed.address == [0; 6];
+            devices.retain(|d| d.ctrl_index != ctrl_index || (!remove_all && d.address != removed));
+        }
 
-        cmd.result()
+        Ok(removed)
     }
 
-    pub fn unpair_device(
+    /// Reads the flags of a device in the accept list, i.e. one added with `add_device`.
+    pub fn get_device_flags(
+        &self,
+        ctrl_index: u16,
+        address: &address::Address,
+    ) -> Result<DeviceFlagsInfo, Error> {
+        self.execute(GetDeviceFlagsCommand::new(
+            ctrl_index,
+            address,
+            self.timeout,
+        ))
+    }
+
+    /// Sets the current flags of a device in the accept list, e.g.
+    /// `DeviceFlags::REMOTE_WAKEUP` to let it wake the system.
+    pub fn set_device_flags(
         &self,
         ctrl_index: u16,
         address: &address::Address,
+        flags: DeviceFlags,
     ) -> Result<address::Address, Error> {
-        let mut cmd = UnpairDeviceCommand::new(ctrl_index, &address, time::Duration::from_secs(1));
-        self.write_command(&mut cmd)?;
+        self.execute(SetDeviceFlagsCommand::new(
+            ctrl_index,
+            address,
+            flags,
+            self.timeout,
+        ))
+    }
 
-        cmd.result()
+    /// Devices added through this handle with `add_device` and not removed since, as
+    /// tracked locally. The kernel offers no command for listing its accept list.
+    pub fn added_devices(&self, ctrl_index: u16) -> Vec<AddedDevice> {
+        match self.added_devices.lock() {
+            Ok(devices) => devices
+                .iter()
+                .filter(|d| d.ctrl_index == ctrl_index)
+                .cloned()
+                .collect(),
+            Err(_) => Vec::new(),
+        }
     }
 
-    pub fn get_supported_cmds(&self) -> Result<SupportedCmdsResult, Error> {
-        let mut cmd = GetSupportedCmdsCommand::new(time::Duration::from_secs(1));
-        self.write_command(&mut cmd)?;
+    pub fn unpair_device(
+        &self,
+        ctrl_index: u16,
+        address: &address::Address,
+    ) -> Result<address::Address, Error> {
+        self.execute(UnpairDeviceCommand::new(ctrl_index, address, self.timeout))
+    }
 
-        cmd.result()
+    pub fn get_supported_cmds(&self) -> Result<SupportedCmdsResult, Error> {
+        self.execute(GetSupportedCmdsCommand::new(self.timeout))
     }
 
     pub fn set_scan_params(
@@ -174,173 +476,559 @@ impl BTMgmt {
         interval: u16,
         window: u16,
     ) -> Result<u8, Error> {
-        let mut cmd =
-            SetScanParamsCommand::new(ctrl_index, interval, window, time::Duration::from_secs(1));
-        self.write_command(&mut cmd)?;
+        self.execute(SetScanParamsCommand::new(
+            ctrl_index,
+            interval,
+            window,
+            self.timeout,
+        ))
+    }
 
-        cmd.result()
+    pub fn disconnect(
+        &self,
+        ctrl_index: u16,
+        address: &address::Address,
+    ) -> Result<address::Address, Error> {
+        self.execute(DisconnectCommand::new(ctrl_index, address, self.timeout))
     }
 
-    fn write_command(&self, cmd: &mut Command) -> Result<(), Error> {
-        let mut fds = vec![libc::pollfd {
-            fd: self.fd,
-            events: libc::POLLIN | libc::POLLHUP | libc::POLLERR,
-            revents: 0,
-        }];
+    /// Disconnects the device and waits up to `timeout` for the matching Device Disconnected
+    /// event. The kernel does not send that event to the socket which issued the disconnect,
+    /// so it is observed on a second control socket opened for the duration of the call.
+    pub fn disconnect_and_wait(
+        &self,
+        ctrl_index: u16,
+        address: &address::Address,
+        timeout: time::Duration,
+    ) -> Result<DisconnectReason, Error> {
+        let observer = BTMgmt::with_transport(self.transport.open_observer()?);
+        observer.set_tracer(self.tracer.lock().ok().and_then(|t| t.clone()));
+        let start = time::Instant::now();
 
-        unsafe {
-            let cmd_ptr = Box::into_raw(cmd.to_bytes().into_boxed_slice());
-            libc::write(self.fd, cmd_ptr as *mut libc::c_void, cmd.size());
-            Box::from_raw(cmd_ptr)
-        };
+        self.execute(DisconnectCommand::new(ctrl_index, address, timeout))?;
+
+        let remaining = timeout
+            .checked_sub(start.elapsed())
+            .unwrap_or_else(|| time::Duration::from_secs(0));
+        let packet = observer.wait_for_event(remaining, |packet| {
+            if packet.controller_index != ctrl_index {
+                return false;
+            }
+
+            match packet.event {
+                event::Event::DeviceDisconnected { address: ref a, .. } => a == address,
+                _ => false,
+            }
+        })?;
+
+        match packet.event {
+            event::Event::DeviceDisconnected { reason, .. } => Ok(reason),
+            _ => Err(Error::UnknownError),
+        }
+    }
+
+    /// Replaces the connection parameters the kernel stores for the given LE devices. Every
+    /// entry is validated before the command is sent.
+    pub fn load_conn_params(
+        &self,
+        ctrl_index: u16,
+        conn_params: &[ConnectionParameters],
+    ) -> Result<(), Error> {
+        self.execute(LoadConnParamsCommand::new(
+            ctrl_index,
+            conn_params,
+            self.timeout,
+        )?)
+    }
+
+    pub fn set_local_name(
+        &self,
+        ctrl_index: u16,
+        name: &str,
+        short_name: &str,
+    ) -> Result<LocalName, Error> {
+        self.execute(SetLocalNameCommand::new(
+            ctrl_index,
+            name,
+            short_name,
+            self.timeout,
+        )?)
+    }
+
+    /// Sets the major and minor device class. The service class bits are derived by the
+    /// kernel from the UUIDs added with `add_uuid`.
+    pub fn set_device_class(
+        &self,
+        ctrl_index: u16,
+        class: &ClassOfDevice,
+    ) -> Result<ClassOfDevice, Error> {
+        self.execute(SetDevClassCommand::new(ctrl_index, class, self.timeout))
+    }
 
-        let start = time::SystemTime::now();
+    pub fn set_appearance(&self, ctrl_index: u16, appearance: u16) -> Result<(), Error> {
+        self.execute(SetAppearanceCommand::new(
+            ctrl_index,
+            appearance,
+            self.timeout,
+        ))
+    }
+
+    pub fn add_uuid(
+        &self,
+        ctrl_index: u16,
+        uuid: &Uuid,
+        svc_hint: u8,
+    ) -> Result<ClassOfDevice, Error> {
+        self.execute(AddUuidCommand::new(
+            ctrl_index,
+            uuid,
+            svc_hint,
+            self.timeout,
+        ))
+    }
+
+    pub fn remove_uuid(&self, ctrl_index: u16, uuid: &Uuid) -> Result<ClassOfDevice, Error> {
+        self.execute(RemoveUuidCommand::new(ctrl_index, uuid, self.timeout))
+    }
+
+    /// Indexes of controllers which need configuration before they can be used.
+    pub fn read_unconf_index_list(&self) -> Result<Vec<u16>, Error> {
+        self.execute(ReadUnconfIndexListCommand::new(self.timeout))
+    }
+
+    pub fn read_controller_config(&self, ctrl_index: u16) -> Result<ControllerConfigInfo, Error> {
+        self.execute(ReadControllerConfigCommand::new(ctrl_index, self.timeout))
+    }
+
+    /// Returns the configuration options still missing after the change.
+    pub fn set_external_config(
+        &self,
+        ctrl_index: u16,
+        enabled: bool,
+    ) -> Result<ConfigOptions, Error> {
+        self.execute(SetExternalConfigCommand::new(
+            ctrl_index,
+            enabled,
+            self.timeout,
+        ))
+    }
+
+    /// Returns the configuration options still missing after the change.
+    pub fn set_public_address(
+        &self,
+        ctrl_index: u16,
+        address: &address::Address,
+    ) -> Result<ConfigOptions, Error> {
+        self.execute(SetPublicAddressCommand::new(
+            ctrl_index,
+            address,
+            self.timeout,
+        ))
+    }
+
+    /// Returns the current settings of the controller.
+    pub fn set_static_address(
+        &self,
+        ctrl_index: u16,
+        address: &address::Address,
+    ) -> Result<Settings, Error> {
+        self.execute(SetStaticAddressCommand::new(
+            ctrl_index,
+            address,
+            self.timeout,
+        )?)
+    }
+
+    /// Reads the BR/EDR Secure Simple Pairing values of the controller. P-256 values are
+    /// only present with Secure Connections enabled.
+    pub fn read_local_oob_data(&self, ctrl_index: u16) -> Result<OobData, Error> {
+        self.execute(ReadLocalOobDataCommand::new(ctrl_index, self.timeout))
+    }
+
+    /// Reads the OOB data for the `oob::OOB_ADDRESS_TYPE_*` mask as EIR structures, which
+    /// cover LE as well.
+    pub fn read_local_oob_ext_data(
+        &self,
+        ctrl_index: u16,
+        address_types: u8,
+    ) -> Result<LocalOobExtData, Error> {
+        self.execute(ReadLocalOobExtDataCommand::new(
+            ctrl_index,
+            address_types,
+            self.timeout,
+        ))
+    }
+
+    pub fn add_remote_oob_data(
+        &self,
+        ctrl_index: u16,
+        address: &address::Address,
+        data: &OobData,
+    ) -> Result<address::Address, Error> {
+        self.execute(AddRemoteOobDataCommand::new(
+            ctrl_index,
+            address,
+            data,
+            self.timeout,
+        )?)
+    }
+
+    pub fn remove_remote_oob_data(
+        &self,
+        ctrl_index: u16,
+        address: &address::Address,
+    ) -> Result<address::Address, Error> {
+        self.execute(RemoveRemoteOobDataCommand::new(
+            ctrl_index,
+            address,
+            self.timeout,
+        ))
+    }
+
+    /// Enables resolvable private addresses using the given local IRK, see
+    /// `Irk::generate`. The controller has to be powered off. Returns the current settings.
+    pub fn set_privacy(
+        &self,
+        ctrl_index: u16,
+        mode: PrivacyMode,
+        irk: &Irk,
+    ) -> Result<Settings, Error> {
+        self.execute(SetPrivacyCommand::new(ctrl_index, mode, irk, self.timeout))
+    }
+
+    /// Returns the current settings.
+    pub fn set_secure_connections(
+        &self,
+        ctrl_index: u16,
+        mode: SecureConnectionsMode,
+    ) -> Result<Settings, Error> {
+        self.execute(SetSecureConnCommand::new(ctrl_index, mode, self.timeout))
+    }
+
+    /// Returns the current settings.
+    pub fn set_debug_keys(&self, ctrl_index: u16, mode: DebugKeysMode) -> Result<Settings, Error> {
+        self.execute(SetDebugKeysCommand::new(ctrl_index, mode, self.timeout))
+    }
+
+    pub fn get_phy_configuration(&self, ctrl_index: u16) -> Result<PhyConfiguration, Error> {
+        self.execute(GetPhyConfigCommand::new(ctrl_index, self.timeout))
+    }
+
+    /// Reads the current PHY configuration and validates `selected` against it before
+    /// sending the new selection.
+    pub fn set_phy_configuration(&self, ctrl_index: u16, selected: Phys) -> Result<(), Error> {
+        self.get_phy_configuration(ctrl_index)?.validate(selected)?;
+
+        self.execute(SetPhyConfigCommand::new(ctrl_index, selected, self.timeout))
+    }
+
+    /// Reads the default system configuration, see `tlv::SystemConfigType` for the known
+    /// parameter types.
+    pub fn read_default_system_config(&self, ctrl_index: u16) -> Result<TlvList, Error> {
+        self.execute(ReadDefConfigCommand::new(
+            ctrl_index,
+            READ_DEF_SYSTEM_CONFIG_OPCODE,
+            self.timeout,
+        ))
+    }
+
+    /// Sets the parameters present in `config`, parameters not listed keep their value.
+    pub fn set_default_system_config(
+        &self,
+        ctrl_index: u16,
+        config: &TlvList,
+    ) -> Result<(), Error> {
+        self.execute(SetDefConfigCommand::new(
+            ctrl_index,
+            SET_DEF_SYSTEM_CONFIG_OPCODE,
+            config,
+            self.timeout,
+        )?)
+    }
+
+    pub fn read_default_runtime_config(&self, ctrl_index: u16) -> Result<TlvList, Error> {
+        self.execute(ReadDefConfigCommand::new(
+            ctrl_index,
+            READ_DEF_RUNTIME_CONFIG_OPCODE,
+            self.timeout,
+        ))
+    }
+
+    pub fn set_default_runtime_config(
+        &self,
+        ctrl_index: u16,
+        config: &TlvList,
+    ) -> Result<(), Error> {
+        self.execute(SetDefConfigCommand::new(
+            ctrl_index,
+            SET_DEF_RUNTIME_CONFIG_OPCODE,
+            config,
+            self.timeout,
+        )?)
+    }
+
+    /// Reads the address, version, settings, class and name of a controller.
+    pub fn read_controller_info(&self, ctrl_index: u16) -> Result<ControllerInfo, Error> {
+        self.execute(ReadControllerInfoCommand::new(ctrl_index, self.timeout))
+    }
+
+    /// Reads the security capabilities, like the maximum encryption key sizes, of a
+    /// controller.
+    pub fn read_controller_capabilities(
+        &self,
+        ctrl_index: u16,
+    ) -> Result<ControllerCapabilities, Error> {
+        self.execute(ReadControllerCapCommand::new(ctrl_index, self.timeout))
+    }
+
+    /// Lists the experimental features of a controller, or the global ones for index
+    /// 0xffff.
+    pub fn read_exp_features(&self, ctrl_index: u16) -> Result<Vec<ExpFeature>, Error> {
+        self.execute(ReadExpFeaturesCommand::new(ctrl_index, self.timeout))
+    }
+
+    /// Enables or disables an experimental feature, see `experimental` for the known UUIDs.
+    pub fn set_exp_feature(
+        &self,
+        ctrl_index: u16,
+        uuid: &Uuid,
+        enabled: bool,
+    ) -> Result<ExpFeature, Error> {
+        self.execute(SetExpFeatureCommand::new(
+            ctrl_index,
+            uuid,
+            enabled,
+            self.timeout,
+        ))
+    }
+
+    pub fn read_adv_monitor_features(&self, ctrl_index: u16) -> Result<AdvMonitorFeatures, Error> {
+        self.execute(ReadAdvMonitorFeaturesCommand::new(ctrl_index, self.timeout))
+    }
+
+    /// Registers a monitor and returns its handle. Matching devices are reported with the
+    /// Adv Monitor Device Found and Device Lost events.
+    pub fn add_adv_monitor(&self, ctrl_index: u16, monitor: &AdvMonitor) -> Result<u16, Error> {
+        self.execute(AddAdvPatternsMonitorCommand::new(
+            ctrl_index,
+            monitor,
+            self.timeout,
+        )?)
+    }
+
+    /// Removes a monitor, or all monitors for handle 0.
+    pub fn remove_adv_monitor(&self, ctrl_index: u16, monitor_handle: u16) -> Result<u16, Error> {
+        self.execute(RemoveAdvMonitorCommand::new(
+            ctrl_index,
+            monitor_handle,
+            self.timeout,
+        ))
+    }
+
+    /// Sends a command and waits for its response, see `Command` for defining commands
+    /// not wrapped by the methods of `BTMgmt`.
+    pub fn execute<C: Command>(&self, cmd: C) -> Result<C::Response, Error> {
+        let response = self.transact(&cmd)?;
+
+        cmd::decode_response(&cmd, &response)
+    }
+
+    /// Sends a command given as raw parameters and returns the status and raw return
+    /// parameters of the response, for prototyping commands `BTMgmt` does not wrap yet.
+    /// A non-zero status is not an error here, see `RawResponse::error`.
+    pub fn send_raw(
+        &self,
+        opcode: u16,
+        ctrl_index: u16,
+        params: &[u8],
+        timeout: time::Duration,
+    ) -> Result<RawResponse, Error> {
+        let response = self.transact(&RawCommand::new(ctrl_index, opcode, params, timeout))?;
+        let (opcode, status, params) = frame::split_response(&response)?;
+
+        Ok(RawResponse {
+            opcode,
+            ctrl_index,
+            status,
+            params: params.to_vec(),
+        })
+    }
+
+    /// Waits up to `timeout` for the next event sent to this socket. Command responses and
+    /// frames which do not decode are skipped.
+    pub fn read_event(&self, timeout: time::Duration) -> Result<Option<event::EventPacket>, Error> {
+        match self.wait_for_event(timeout, |packet| {
+            packet.event_code != u16::from(COMMAND_RESPONSE_EVENT)
+                && packet.event_code != u16::from(COMMAND_STATUS_EVENT)
+        }) {
+            Ok(packet) => Ok(Some(packet)),
+            Err(Error::LocalTimeout) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    // writes the command and returns its response frame
+    fn transact<C: Command>(&self, cmd: &C) -> Result<Vec<u8>, Error> {
+        self.write(&cmd::to_frame(cmd)?)?;
+
+        let start = time::Instant::now();
         loop {
-            let r = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::c_ulong, 1) };
-            if r > 0 && fds[0].revents > 0 {
-                if fds[0].revents & libc::POLLIN > 0 {
-                    let mut buffer: [u8; 1024] = [0; 1024];
-                    let bytes = unsafe {
-                        libc::read(self.fd, buffer.as_mut_ptr() as *mut libc::c_void, 1024)
-                    };
-
-                    if bytes <= 0 {
-                        return Err(error::Error::UnknownError);
-                    }
+            let mut buffer: [u8; 1024] = [0; 1024];
+            let bytes = self.read(&mut buffer, time::Duration::from_millis(1))?;
 
-                    if (buffer[0] == COMMAND_RESPONSE_EVENT || buffer[0] == COMMAND_STATUS_EVENT)
-                        && cmd.is_response(&buffer[0..buffer.len()])
-                    {
-                        let mut v = Vec::new();
-                        v.extend_from_slice(&buffer);
-                        cmd.store_response(v);
-                        return Ok(());
-                    }
-                } else {
-                    return Err(error::Error::UnknownError);
-                }
+            if cmd::is_response(cmd, &buffer[0..bytes]) {
+                return Ok(buffer[0..bytes].to_vec());
+            }
+
+            if start.elapsed() > cmd.timeout() {
+                return Err(Error::LocalTimeout);
             }
+        }
+    }
+
+    fn wait_for_event<F>(
+        &self,
+        timeout: time::Duration,
+        predicate: F,
+    ) -> Result<event::EventPacket, Error>
+    where
+        F: Fn(&event::EventPacket) -> bool,
+    {
+        let start = time::Instant::now();
+        loop {
+            let mut buffer: [u8; 1024] = [0; 1024];
+            let bytes = self.read(&mut buffer, time::Duration::from_millis(1))?;
 
-            // check command timeout
-            match start.elapsed() {
-                Ok(elapsed) => {
-                    if elapsed > cmd.get_timeout() {
-                        return Err(error::Error::Timeout);
+            if bytes > 0 {
+                if let Some(packet) = event::EventPacket::from_bytes(&buffer[0..bytes]) {
+                    if predicate(&packet) {
+                        return Ok(packet);
                     }
                 }
+            }
 
-                Err(_) => {
-                    return Err(error::Error::UnknownError);
-                }
+            if start.elapsed() > timeout {
+                return Err(Error::LocalTimeout);
             }
         }
     }
-}
 
-impl Drop for BTMgmt {
-    fn drop(&mut self) {
-        unsafe {
-            libc::close(self.fd);
+    fn write(&self, frame: &[u8]) -> Result<(), Error> {
+        self.trace(btsnoop::Direction::Sent, frame);
+        self.transport.write(frame)
+    }
+
+    fn read(&self, buffer: &mut [u8], timeout: time::Duration) -> Result<usize, Error> {
+        let bytes = self.transport.read(buffer, timeout)?;
+        self.trace(btsnoop::Direction::Received, &buffer[0..bytes]);
+
+        Ok(bytes)
+    }
+
+    fn trace(&self, direction: btsnoop::Direction, frame: &[u8]) {
+        if let Ok(tracer) = self.tracer.lock() {
+            if let Some(ref tracer) = *tracer {
+                tracer.record(direction, frame);
+            }
         }
     }
 }
 
+enum EventSink {
+    AuthFailures(mpsc::SyncSender<Box<BtmgmtEventPacketStructure>>),
+    All(mpsc::SyncSender<Box<event::EventPacket>>),
+}
+
 pub struct BTMgmtEventListener {
     pub fd: i32,
     running: Arc<AtomicBool>,
     handle: Option<std::thread::JoinHandle<()>>,
+    tracer: Arc<Mutex<Option<btsnoop::Tracer>>>,
 }
 
 impl BTMgmtEventListener {
+    /// Forwards only Device Disconnected events with an authentication failure reason.
     pub fn new(
         event_tx: mpsc::SyncSender<Box<BtmgmtEventPacketStructure>>,
     ) -> Result<BTMgmtEventListener, Error> {
+        BTMgmtEventListener::open(EventSink::AuthFailures(event_tx))
+    }
+
+    /// Forwards every mgmt event received on the control channel.
+    pub fn with_events(
+        event_tx: mpsc::SyncSender<Box<event::EventPacket>>,
+    ) -> Result<BTMgmtEventListener, Error> {
+        BTMgmtEventListener::open(EventSink::All(event_tx))
+    }
+
+    fn open(sink: EventSink) -> Result<BTMgmtEventListener, Error> {
         let mut btmgmteventlistener = BTMgmtEventListener {
-            fd: unsafe {
-                libc::socket(
-                    libc::PF_BLUETOOTH,
-                    libc::SOCK_RAW | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
-                    BTPROTO_HCI,
-                )
-            },
+            fd: socket::open(HCI_DEV_NONE, HCI_CHANNEL_CONTROL)?,
             running: Arc::new(AtomicBool::new(false)),
             handle: None,
+            tracer: Arc::new(Mutex::new(None)),
         };
 
-        if btmgmteventlistener.fd < 0 {
-            return Err(Error::SocketError);
-        }
-
-        let addr = SockAddrHci {
-            hci_family: libc::AF_BLUETOOTH as u16,
-            hci_dev: HCI_DEV_NONE,
-            hci_channel: HCI_CHANNEL_CONTROL,
-        };
-
-        if unsafe {
-            let addr_ptr = Box::into_raw(Box::new(addr));
-            let ret_val = libc::bind(
-                btmgmteventlistener.fd,
-                addr_ptr as *const libc::sockaddr,
-                std::mem::size_of::<SockAddrHci>() as u32,
-            );
-            Box::from_raw(addr_ptr);
-            ret_val
-        } < 0
-        {
-            return Err(Error::BindError);
-        }
-
-        btmgmteventlistener.run(event_tx);
+        btmgmteventlistener.run(sink);
         Ok(btmgmteventlistener)
     }
 
-    fn run(&mut self, event_tx: mpsc::SyncSender<Box<BtmgmtEventPacketStructure>>) {
-        let mut fds = vec![libc::pollfd {
-            fd: self.fd,
-            events: libc::POLLIN | libc::POLLHUP | libc::POLLERR,
-            revents: 0,
-        }];
+    /// Records every frame the listener receives to `tracer`, or stops recording when
+    /// `None`.
+    pub fn set_tracer(&self, tracer: Option<btsnoop::Tracer>) {
+        if let Ok(mut current) = self.tracer.lock() {
+            *current = tracer;
+        }
+    }
 
+    fn run(&mut self, sink: EventSink) {
         self.running.store(true, Ordering::Relaxed);
         let running = self.running.clone();
-        let fd = self.fd.clone();
+        let tracer = self.tracer.clone();
+        let fd = self.fd;
 
         let handle = std::thread::spawn(move || {
             while running.load(Ordering::Relaxed) {
-                let r = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::c_ulong, 1) };
-                if r > 0 && fds[0].revents > 0 {
-                    if fds[0].revents & libc::POLLIN > 0 {
-                        let mut buffer: [u8; 128] = [0; 128];
-                        let bytes = unsafe {
-                            libc::read(fd, buffer.as_mut_ptr() as *mut libc::c_void, 128)
-                        };
-
-                        if bytes as usize == mem::size_of::<BtmgmtEventPacketStructure>() {
-                            let mut address: [u8; 6] = Default::default();
-                            address.copy_from_slice(&buffer[6..12]);
-
-                            let btmgmtstr = BtmgmtEventPacketStructure {
-                                event_code: u16::from(buffer[0]) | u16::from(buffer[1]) << 8,
-                                controller_index: u16::from(buffer[2]) | u16::from(buffer[3]) << 8,
-                                param_lenght: u16::from(buffer[4]) | u16::from(buffer[5]) << 8,
-                                device_address: address::Address::from_bytes(address, buffer[12]),
-                                disconnect_reason: buffer[13],
-                            };
-
-                            if btmgmtstr.event_code == BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED
-                                && btmgmtstr.disconnect_reason
-                                    == BTMGMT_EVENT_DEVICE_DISCONNECTED_REASON_AUTH_FAILURE
-                            {
-                                let event_data = Box::new(btmgmtstr);
-                                match event_tx.send(event_data) {
-                                    Ok(()) => {}
-                                    Err(_err) => return,
-                                }
-                            }
-                        }
+                let mut buffer: [u8; 1024] = [0; 1024];
+                let bytes = match socket::read(fd, &mut buffer, time::Duration::from_millis(1)) {
+                    Ok(0) | Err(_) => continue,
+                    Ok(bytes) => bytes,
+                };
+
+                if let Ok(tracer) = tracer.lock() {
+                    if let Some(ref tracer) = *tracer {
+                        tracer.record(btsnoop::Direction::Received, &buffer[0..bytes]);
                     }
                 }
+
+                let packet = match event::EventPacket::from_bytes(&buffer[0..bytes]) {
+                    Some(packet) => packet,
+                    None => continue,
+                };
+
+                let sent = match sink {
+                    EventSink::AuthFailures(ref event_tx) => match packet.event {
+                        event::Event::DeviceDisconnected { address, reason }
+                            if reason == DisconnectReason::AuthFailure =>
+                        {
+                            event_tx
+                                .send(Box::new(BtmgmtEventPacketStructure {
+                                    event_code: packet.event_code,
+                                    controller_index: packet.controller_index,
+                                    param_lenght: (bytes - 6) as u16,
+                                    device_address: address,
+                                    disconnect_reason: reason,
+                                }))
+                                .is_ok()
+                        }
+                        _ => true,
+                    },
+                    EventSink::All(ref event_tx) => event_tx.send(Box::new(packet)).is_ok(),
+                };
+
+                if !sent {
+                    return;
+                }
             }
         });
 
@@ -352,8 +1040,6 @@ impl Drop for BTMgmtEventListener {
     fn drop(&mut self) {
         self.running.store(false, Ordering::Relaxed);
         self.handle.take().unwrap().join().unwrap();
-        unsafe {
-            libc::close(self.fd);
-        }
+        socket::close(self.fd);
     }
 }
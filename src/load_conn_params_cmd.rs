@@ -0,0 +1,179 @@
+use address::{Address, AddressType};
+use error::Error;
+
+use std::time;
+
+pub const LOAD_CONN_PARAMS_OPCODE: u16 = 0x0035;
+
+// connection interval in units of 1.25ms, 7.5ms to 4s
+pub const CONN_INTERVAL_MIN: u16 = 0x0006;
+pub const CONN_INTERVAL_MAX: u16 = 0x0c80;
+// number of connection events
+pub const CONN_LATENCY_MAX: u16 = 0x01f3;
+// supervision timeout in units of 10ms, 100ms to 32s
+pub const SUPERVISION_TIMEOUT_MIN: u16 = 0x000a;
+pub const SUPERVISION_TIMEOUT_MAX: u16 = 0x0c80;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionParameters {
+    pub address: Address,
+    pub min_interval: u16,
+    pub max_interval: u16,
+    pub latency: u16,
+    pub supervision_timeout: u16,
+}
+
+impl ConnectionParameters {
+    pub fn new(
+        address: &Address,
+        min_interval: u16,
+        max_interval: u16,
+        latency: u16,
+        supervision_timeout: u16,
+    ) -> Result<ConnectionParameters, Error> {
+        let params = ConnectionParameters {
+            address: address.clone(),
+            min_interval,
+            max_interval,
+            latency,
+            supervision_timeout,
+        };
+
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Checks the values against the ranges allowed by the Core specification (Vol 4, Part E,
+    /// 7.8.12), the same checks the kernel applies before accepting the parameters.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self.address.address_type {
+            AddressType::LePublic | AddressType::LeRandom => {}
+            _ => return Err(Error::InvalidParameters),
+        }
+
+        if self.min_interval < CONN_INTERVAL_MIN
+            || self.max_interval > CONN_INTERVAL_MAX
+            || self.min_interval > self.max_interval
+        {
+            return Err(Error::InvalidParameters);
+        }
+
+        if self.latency > CONN_LATENCY_MAX {
+            return Err(Error::InvalidParameters);
+        }
+
+        if self.supervision_timeout < SUPERVISION_TIMEOUT_MIN
+            || self.supervision_timeout > SUPERVISION_TIMEOUT_MAX
+        {
+            return Err(Error::InvalidParameters);
+        }
+
+        // the supervision timeout must be larger than (1 + latency) * max_interval * 2,
+        // which in the respective units is timeout * 4 > (1 + latency) * max_interval
+        if u32::from(self.supervision_timeout) * 4
+            <= (1 + u32::from(self.latency)) * u32::from(self.max_interval)
+        {
+            return Err(Error::InvalidParameters);
+        }
+
+        Ok(())
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<ConnectionParameters> {
+        if data.len() < 15 {
+            return None;
+        }
+
+        let mut address: [u8; 6] = Default::default();
+        address.copy_from_slice(&data[0..6]);
+
+        Some(ConnectionParameters {
+            address: Address::from_bytes(address, data[6]),
+            min_interval: u16::from(data[7]) | (u16::from(data[8]) << 8),
+            max_interval: u16::from(data[9]) | (u16::from(data[10]) << 8),
+            latency: u16::from(data[11]) | (u16::from(data[12]) << 8),
+            supervision_timeout: u16::from(data[13]) | (u16::from(data[14]) << 8),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(15);
+        v.extend_from_slice(&self.address.address);
+        v.push(self.address.address_type.to_byte());
+        for value in &[
+            self.min_interval,
+            self.max_interval,
+            self.latency,
+            self.supervision_timeout,
+        ] {
+            v.push((value & 0xff) as u8);
+            v.push((value >> 8 & 0xff) as u8);
+        }
+
+        v
+    }
+}
+
+command! {
+    pub struct LoadConnParamsCommand(LOAD_CONN_PARAMS_OPCODE) -> () {
+        |_| Ok(())
+    }
+}
+
+impl LoadConnParamsCommand {
+    pub fn new(
+        ctrl_index: u16,
+        conn_params: &[ConnectionParameters],
+        timeout: time::Duration,
+    ) -> Result<LoadConnParamsCommand, Error> {
+        // param_length is a u16, which leaves room for at most 4368 entries
+        if conn_params.len() > (0xffff - 2) / 15 {
+            return Err(Error::InvalidParameters);
+        }
+
+        for p in conn_params {
+            p.validate()?;
+        }
+
+        let mut params = Vec::new();
+
+        let count = conn_params.len() as u16;
+        params.push((count & 0xff) as u8);
+        params.push((count >> 8 & 0xff) as u8);
+        for p in conn_params {
+            params.append(&mut p.to_bytes());
+        }
+
+        Ok(LoadConnParamsCommand::request(ctrl_index, params, timeout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let a = Address::from_string("AB:BC:CD:DE:EF:F1", AddressType::LeRandom).unwrap();
+        assert!(ConnectionParameters::new(&a, 0x0018, 0x0028, 0, 0x002a).is_ok());
+        // interval below 7.5ms
+        assert!(ConnectionParameters::new(&a, 0x0005, 0x0028, 0, 0x002a).is_err());
+        // min above max
+        assert!(ConnectionParameters::new(&a, 0x0030, 0x0028, 0, 0x002a).is_err());
+        // supervision timeout too short for the interval and latency
+        assert!(ConnectionParameters::new(&a, 0x0018, 0x0028, 4, 0x002a).is_err());
+
+        let b = Address::from_string("AB:BC:CD:DE:EF:F1", AddressType::BrEdr).unwrap();
+        assert!(ConnectionParameters::new(&b, 0x0018, 0x0028, 0, 0x002a).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let a = Address::from_string("AB:BC:CD:DE:EF:F1", AddressType::LePublic).unwrap();
+        let p = ConnectionParameters::new(&a, 0x0018, 0x0028, 1, 0x01f4).unwrap();
+        assert_eq!(
+            Some(p.clone()),
+            ConnectionParameters::from_bytes(&p.to_bytes())
+        );
+    }
+}
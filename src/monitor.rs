@@ -0,0 +1,361 @@
+//! Reader for the HCI monitor channel, the stream `btmon` decodes. It carries the HCI
+//! traffic of every controller plus the mgmt commands and events of every control socket.
+
+use error::Error;
+use event::EventPacket;
+use frame::{le16, le32};
+use socket::{self, HCI_CHANNEL_MONITOR, HCI_DEV_NONE};
+
+use std::time;
+
+const MONITOR_HEADER_SIZE: usize = 6;
+/// Large enough for the biggest HCI packet plus the monitor header.
+const MONITOR_BUFFER_SIZE: usize = MONITOR_HEADER_SIZE + 4 + 0xffff;
+
+const OPCODE_NEW_INDEX: u16 = 0;
+const OPCODE_DEL_INDEX: u16 = 1;
+const OPCODE_COMMAND_PKT: u16 = 2;
+const OPCODE_EVENT_PKT: u16 = 3;
+const OPCODE_ACL_TX_PKT: u16 = 4;
+const OPCODE_ACL_RX_PKT: u16 = 5;
+const OPCODE_SCO_TX_PKT: u16 = 6;
+const OPCODE_SCO_RX_PKT: u16 = 7;
+const OPCODE_OPEN_INDEX: u16 = 8;
+const OPCODE_CLOSE_INDEX: u16 = 9;
+const OPCODE_INDEX_INFO: u16 = 10;
+const OPCODE_VENDOR_DIAG: u16 = 11;
+const OPCODE_SYSTEM_NOTE: u16 = 12;
+const OPCODE_USER_LOGGING: u16 = 13;
+const OPCODE_CTRL_OPEN: u16 = 14;
+const OPCODE_CTRL_CLOSE: u16 = 15;
+const OPCODE_CTRL_COMMAND: u16 = 16;
+const OPCODE_CTRL_EVENT: u16 = 17;
+const OPCODE_ISO_TX_PKT: u16 = 18;
+const OPCODE_ISO_RX_PKT: u16 = 19;
+
+#[derive(Debug, Clone)]
+pub enum MonitorPacket {
+    NewIndex {
+        /// 0x00 for a primary controller, 0x01 for AMP.
+        controller_type: u8,
+        bus: u8,
+        address: [u8; 6],
+        name: String,
+    },
+    DelIndex,
+    /// HCI packets without the H4 packet type byte.
+    Command(Vec<u8>),
+    Event(Vec<u8>),
+    AclTx(Vec<u8>),
+    AclRx(Vec<u8>),
+    ScoTx(Vec<u8>),
+    ScoRx(Vec<u8>),
+    IsoTx(Vec<u8>),
+    IsoRx(Vec<u8>),
+    OpenIndex,
+    CloseIndex,
+    IndexInfo {
+        address: [u8; 6],
+        manufacturer: u16,
+    },
+    VendorDiag(Vec<u8>),
+    SystemNote(String),
+    UserLogging {
+        priority: u8,
+        ident: String,
+        message: String,
+    },
+    /// A control socket was opened, e.g. by `bluetoothd` or this library.
+    CtrlOpen {
+        cookie: u32,
+        format: u16,
+        version: u8,
+        revision: u16,
+        flags: u32,
+        command: String,
+    },
+    CtrlClose {
+        cookie: u32,
+    },
+    /// An mgmt command written to the control socket identified by `cookie`.
+    CtrlCommand {
+        cookie: u32,
+        opcode: u16,
+        params: Vec<u8>,
+    },
+    /// An mgmt event sent to the control socket identified by `cookie`.
+    CtrlEvent {
+        cookie: u32,
+        event: EventPacket,
+    },
+    Unknown {
+        opcode: u16,
+        data: Vec<u8>,
+    },
+}
+
+impl MonitorPacket {
+    /// Decodes the payload of a monitor packet, `index` is the controller index of its
+    /// header. Returns `None` if the payload is too short for the opcode.
+    pub fn from_bytes(opcode: u16, index: u16, data: &[u8]) -> Option<MonitorPacket> {
+        let packet = match opcode {
+            OPCODE_NEW_INDEX if data.len() >= 16 => MonitorPacket::NewIndex {
+                controller_type: data[0],
+                bus: data[1],
+                address: bdaddr(&data[2..8]),
+                name: c_string(&data[8..16]),
+            },
+            OPCODE_DEL_INDEX => MonitorPacket::DelIndex,
+            OPCODE_COMMAND_PKT => MonitorPacket::Command(data.to_vec()),
+            OPCODE_EVENT_PKT => MonitorPacket::Event(data.to_vec()),
+            OPCODE_ACL_TX_PKT => MonitorPacket::AclTx(data.to_vec()),
+            OPCODE_ACL_RX_PKT => MonitorPacket::AclRx(data.to_vec()),
+            OPCODE_SCO_TX_PKT => MonitorPacket::ScoTx(data.to_vec()),
+            OPCODE_SCO_RX_PKT => MonitorPacket::ScoRx(data.to_vec()),
+            OPCODE_ISO_TX_PKT => MonitorPacket::IsoTx(data.to_vec()),
+            OPCODE_ISO_RX_PKT => MonitorPacket::IsoRx(data.to_vec()),
+            OPCODE_OPEN_INDEX => MonitorPacket::OpenIndex,
+            OPCODE_CLOSE_INDEX => MonitorPacket::CloseIndex,
+            OPCODE_INDEX_INFO if data.len() >= 8 => MonitorPacket::IndexInfo {
+                address: bdaddr(&data[0..6]),
+                manufacturer: le16(&data[6..8]),
+            },
+            OPCODE_VENDOR_DIAG => MonitorPacket::VendorDiag(data.to_vec()),
+            OPCODE_SYSTEM_NOTE => MonitorPacket::SystemNote(c_string(data)),
+            OPCODE_USER_LOGGING if data.len() >= 2 => {
+                // the ident length includes its terminating NUL
+                let ident_end = 2 + data[1] as usize;
+                if data.len() < ident_end {
+                    return None;
+                }
+
+                MonitorPacket::UserLogging {
+                    priority: data[0],
+                    ident: c_string(&data[2..ident_end]),
+                    message: c_string(&data[ident_end..]),
+                }
+            }
+            OPCODE_CTRL_OPEN if data.len() >= 14 => {
+                let command_end = 14 + data[13] as usize;
+                if data.len() < command_end {
+                    return None;
+                }
+
+                MonitorPacket::CtrlOpen {
+                    cookie: le32(&data[0..4]),
+                    format: le16(&data[4..6]),
+                    version: data[6],
+                    revision: le16(&data[7..9]),
+                    flags: le32(&data[9..13]),
+                    command: c_string(&data[14..command_end]),
+                }
+            }
+            OPCODE_CTRL_CLOSE if data.len() >= 4 => MonitorPacket::CtrlClose {
+                cookie: le32(&data[0..4]),
+            },
+            OPCODE_CTRL_COMMAND if data.len() >= 6 => MonitorPacket::CtrlCommand {
+                cookie: le32(&data[0..4]),
+                opcode: le16(&data[4..6]),
+                params: data[6..].to_vec(),
+            },
+            OPCODE_CTRL_EVENT if data.len() >= 6 && data.len() - 6 <= 0xffff => {
+                // rebuild the mgmt frame the control socket received
+                let mut frame = Vec::with_capacity(data.len());
+                frame.extend_from_slice(&data[4..6]);
+                frame.extend_from_slice(&index.to_le_bytes());
+                frame.extend_from_slice(&((data.len() - 6) as u16).to_le_bytes());
+                frame.extend_from_slice(&data[6..]);
+
+                MonitorPacket::CtrlEvent {
+                    cookie: le32(&data[0..4]),
+                    event: EventPacket::from_bytes(&frame)?,
+                }
+            }
+            OPCODE_NEW_INDEX | OPCODE_INDEX_INFO | OPCODE_USER_LOGGING | OPCODE_CTRL_OPEN
+            | OPCODE_CTRL_CLOSE | OPCODE_CTRL_COMMAND | OPCODE_CTRL_EVENT => return None,
+            _ => MonitorPacket::Unknown {
+                opcode,
+                data: data.to_vec(),
+            },
+        };
+
+        Some(packet)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MonitorRecord {
+    /// Time the record was read from the socket.
+    pub timestamp: time::SystemTime,
+    /// Controller index, `0xffff` for records not tied to a controller.
+    pub index: u16,
+    pub packet: MonitorPacket,
+}
+
+impl MonitorRecord {
+    /// Decodes a monitor frame, header included. Returns `None` for truncated frames.
+    pub fn from_bytes(data: &[u8], timestamp: time::SystemTime) -> Option<MonitorRecord> {
+        if data.len() < MONITOR_HEADER_SIZE {
+            return None;
+        }
+
+        let opcode = le16(&data[0..2]);
+        let index = le16(&data[2..4]);
+        let length = le16(&data[4..6]) as usize;
+        if data.len() < MONITOR_HEADER_SIZE + length {
+            return None;
+        }
+
+        let payload = &data[MONITOR_HEADER_SIZE..MONITOR_HEADER_SIZE + length];
+        Some(MonitorRecord {
+            timestamp,
+            index,
+            packet: MonitorPacket::from_bytes(opcode, index, payload)?,
+        })
+    }
+}
+
+/// Socket bound to the HCI monitor channel, which needs `CAP_NET_RAW`. Iterating blocks
+/// until the next record arrives.
+pub struct Monitor {
+    pub fd: i32,
+}
+
+impl Monitor {
+    pub fn open() -> Result<Monitor, Error> {
+        Ok(Monitor {
+            fd: socket::open(HCI_DEV_NONE, HCI_CHANNEL_MONITOR)?,
+        })
+    }
+
+    /// Waits up to `timeout` for the next record. Returns `None` when nothing decodable
+    /// arrived in time.
+    pub fn read(&self, timeout: time::Duration) -> Result<Option<MonitorRecord>, Error> {
+        let mut buffer = vec![0u8; MONITOR_BUFFER_SIZE];
+        let bytes = socket::read(self.fd, &mut buffer, timeout)?;
+
+        Ok(MonitorRecord::from_bytes(
+            &buffer[0..bytes],
+            time::SystemTime::now(),
+        ))
+    }
+}
+
+impl Iterator for Monitor {
+    type Item = Result<MonitorRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.read(time::Duration::from_secs(1)) {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        socket::close(self.fd);
+    }
+}
+
+fn bdaddr(data: &[u8]) -> [u8; 6] {
+    let mut address: [u8; 6] = Default::default();
+    address.copy_from_slice(&data[0..6]);
+    address
+}
+
+// reads up to the first NUL, the kernel pads names and notes with them
+fn c_string(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[0..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use event::Event;
+
+    fn record(opcode: u16, index: u16, payload: &[u8]) -> Option<MonitorRecord> {
+        let mut data = vec![0u8; MONITOR_HEADER_SIZE];
+        data[0..2].copy_from_slice(&opcode.to_le_bytes());
+        data[2..4].copy_from_slice(&index.to_le_bytes());
+        data[4..6].copy_from_slice(&(payload.len() as u16).to_le_bytes());
+        data.extend_from_slice(payload);
+
+        MonitorRecord::from_bytes(&data, time::UNIX_EPOCH)
+    }
+
+    #[test]
+    fn decodes_records() {
+        let new_index = record(
+            OPCODE_NEW_INDEX,
+            0,
+            b"\x00\x01\xf1\xef\xde\xcd\xbc\xc1hci0\0\0\0\0",
+        )
+        .unwrap();
+        assert_eq!(0, new_index.index);
+        match new_index.packet {
+            MonitorPacket::NewIndex {
+                controller_type,
+                bus,
+                address,
+                ref name,
+            } => {
+                assert_eq!((0, 1), (controller_type, bus));
+                assert_eq!([0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xc1], address);
+                assert_eq!("hci0", name);
+            }
+            ref packet => panic!("unexpected packet {:?}", packet),
+        }
+
+        match record(
+            OPCODE_USER_LOGGING,
+            0xffff,
+            b"\x06\x0bbluetoothd\0started\0",
+        )
+        .unwrap()
+        .packet
+        {
+            MonitorPacket::UserLogging {
+                priority,
+                ref ident,
+                ref message,
+            } => {
+                assert_eq!(6, priority);
+                assert_eq!("bluetoothd", ident);
+                assert_eq!("started", message);
+            }
+            ref packet => panic!("unexpected packet {:?}", packet),
+        }
+
+        // Local Name Changed sent to the control socket with cookie 7
+        let mut payload = vec![0x07, 0x00, 0x00, 0x00, 0x08, 0x00];
+        payload.extend_from_slice(&[0u8; 260]);
+        payload[6..10].copy_from_slice(b"name");
+        match record(OPCODE_CTRL_EVENT, 1, &payload).unwrap().packet {
+            MonitorPacket::CtrlEvent { cookie, ref event } => {
+                assert_eq!(7, cookie);
+                assert_eq!(1, event.controller_index);
+                assert!(matches!(event.event, Event::LocalNameChanged(_)));
+            }
+            ref packet => panic!("unexpected packet {:?}", packet),
+        }
+
+        assert!(matches!(
+            record(OPCODE_ACL_RX_PKT, 0, &[0x01, 0x20]).unwrap().packet,
+            MonitorPacket::AclRx(ref data) if data == &[0x01, 0x20]
+        ));
+        assert!(matches!(
+            record(0x1234, 0, &[]).unwrap().packet,
+            MonitorPacket::Unknown { opcode: 0x1234, .. }
+        ));
+        assert!(record(OPCODE_NEW_INDEX, 0, &[0x00, 0x01]).is_none());
+        assert!(record(OPCODE_USER_LOGGING, 0, &[0x06, 0x0b, 0x62]).is_none());
+        assert!(
+            MonitorRecord::from_bytes(&[0x02, 0x00, 0x00, 0x00, 0x04], time::UNIX_EPOCH).is_none()
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use address::{Address, AddressType};
+
+// bit mask of address types accepted by Read Local OOB Extended Data
+pub const OOB_ADDRESS_TYPE_BREDR: u8 = 0x01;
+pub const OOB_ADDRESS_TYPE_LE_PUBLIC: u8 = 0x02;
+pub const OOB_ADDRESS_TYPE_LE_RANDOM: u8 = 0x04;
+pub const OOB_ADDRESS_TYPE_LE: u8 = OOB_ADDRESS_TYPE_LE_PUBLIC | OOB_ADDRESS_TYPE_LE_RANDOM;
+
+pub const EIR_TYPE_CLASS_OF_DEV: u8 = 0x0d;
+pub const EIR_TYPE_SSP_HASH_C192: u8 = 0x0e;
+pub const EIR_TYPE_SSP_RAND_R192: u8 = 0x0f;
+pub const EIR_TYPE_LE_BDADDR: u8 = 0x1b;
+pub const EIR_TYPE_LE_ROLE: u8 = 0x1c;
+pub const EIR_TYPE_SSP_HASH_C256: u8 = 0x1d;
+pub const EIR_TYPE_SSP_RAND_R256: u8 = 0x1e;
+pub const EIR_TYPE_LE_SC_CONFIRM: u8 = 0x22;
+pub const EIR_TYPE_LE_SC_RANDOM: u8 = 0x23;
+
+/// LE role carried by LE OOB data, which the Core Specification Supplement requires for
+/// LE OOB pairing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeRole {
+    PeripheralOnly,
+    CentralOnly,
+    PeripheralPreferred,
+    CentralPreferred,
+}
+
+impl LeRole {
+    /// Returns `None` for reserved values.
+    pub fn from_byte(value: u8) -> Option<LeRole> {
+        match value {
+            0x00 => Some(LeRole::PeripheralOnly),
+            0x01 => Some(LeRole::CentralOnly),
+            0x02 => Some(LeRole::PeripheralPreferred),
+            0x03 => Some(LeRole::CentralPreferred),
+            _ => None,
+        }
+    }
+
+    pub fn to_byte(self) -> u8 {
+        match self {
+            LeRole::PeripheralOnly => 0x00,
+            LeRole::CentralOnly => 0x01,
+            LeRole::PeripheralPreferred => 0x02,
+            LeRole::CentralPreferred => 0x03,
+        }
+    }
+
+    /// Finds the LE Role structure in a sequence of EIR/AD structures.
+    pub fn from_eir(eir: &[u8]) -> Option<LeRole> {
+        EirIterator::new(eir)
+            .find(|&(eir_type, value)| eir_type == EIR_TYPE_LE_ROLE && value.len() == 1)
+            .and_then(|(_, value)| LeRole::from_byte(value[0]))
+    }
+}
+
+/// Hash (confirmation value) and randomizer of one pairing curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OobValues {
+    pub hash: [u8; 16],
+    pub randomizer: [u8; 16],
+}
+
+impl OobValues {
+    fn from_bytes(data: &[u8]) -> Option<OobValues> {
+        if data.len() < 32 || data[0..32].iter().all(|b| *b == 0) {
+            return None;
+        }
+
+        let mut hash: [u8; 16] = Default::default();
+        let mut randomizer: [u8; 16] = Default::default();
+        hash.copy_from_slice(&data[0..16]);
+        randomizer.copy_from_slice(&data[16..32]);
+
+        Some(OobValues { hash, randomizer })
+    }
+}
+
+/// Out-of-band pairing data. P-192 values are used by legacy Secure Simple Pairing on
+/// BR/EDR only, P-256 values by Secure Connections on BR/EDR and LE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OobData {
+    pub p192: Option<OobValues>,
+    pub p256: Option<OobValues>,
+}
+
+impl OobData {
+    /// Decodes the P-192 hash and randomizer optionally followed by the P-256 ones, as
+    /// used by Read Local OOB Data and Add Remote OOB Data. All-zero values are absent.
+    pub fn from_bytes(data: &[u8]) -> OobData {
+        OobData {
+            p192: OobValues::from_bytes(data),
+            p256: if data.len() >= 64 {
+                OobValues::from_bytes(&data[32..64])
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(64);
+        for values in &[self.p192, self.p256] {
+            match *values {
+                Some(values) => {
+                    v.extend_from_slice(&values.hash);
+                    v.extend_from_slice(&values.randomizer);
+                }
+                None => v.extend_from_slice(&[0; 32]),
+            }
+        }
+
+        // the short form carries P-192 values only
+        if self.p256.is_none() {
+            v.truncate(32);
+        }
+
+        v
+    }
+
+    /// Collects the hash and randomizer values from a sequence of EIR/AD structures,
+    /// e.g. the EIR data returned by Read Local OOB Extended Data.
+    pub fn from_eir(eir: &[u8]) -> OobData {
+        let mut c192 = None;
+        let mut r192 = None;
+        let mut c256 = None;
+        let mut r256 = None;
+
+        for (eir_type, value) in EirIterator::new(eir) {
+            if value.len() != 16 {
+                continue;
+            }
+
+            let mut v: [u8; 16] = Default::default();
+            v.copy_from_slice(value);
+            match eir_type {
+                EIR_TYPE_SSP_HASH_C192 => c192 = Some(v),
+                EIR_TYPE_SSP_RAND_R192 => r192 = Some(v),
+                EIR_TYPE_SSP_HASH_C256 | EIR_TYPE_LE_SC_CONFIRM => c256 = Some(v),
+                EIR_TYPE_SSP_RAND_R256 | EIR_TYPE_LE_SC_RANDOM => r256 = Some(v),
+                _ => {}
+            }
+        }
+
+        OobData {
+            p192: c192.map(|hash| OobValues {
+                hash,
+                randomizer: r192.unwrap_or_default(),
+            }),
+            p256: c256.map(|hash| OobValues {
+                hash,
+                randomizer: r256.unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// Encodes the data as the OOB blob exchanged over e.g. NFC. For a BR/EDR address this
+    /// is the Secure Simple Pairing OOB format, a two byte total length and the address
+    /// followed by EIR structures. For an LE address it is a sequence of AD structures
+    /// starting with the LE Bluetooth Device Address and `role`; P-192 values are not used
+    /// on LE and are left out. `role` is ignored for BR/EDR.
+    pub fn to_eir(&self, address: &Address, role: LeRole) -> Vec<u8> {
+        let mut eir = Vec::new();
+
+        match address.address_type {
+            AddressType::LePublic | AddressType::LeRandom => {
+                let mut le_address = address.address.to_vec();
+                le_address.push(match address.address_type {
+                    AddressType::LeRandom => 1,
+                    _ => 0,
+                });
+                push_eir(&mut eir, EIR_TYPE_LE_BDADDR, &le_address);
+                push_eir(&mut eir, EIR_TYPE_LE_ROLE, &[role.to_byte()]);
+
+                if let Some(values) = self.p256 {
+                    push_eir(&mut eir, EIR_TYPE_LE_SC_CONFIRM, &values.hash);
+                    push_eir(&mut eir, EIR_TYPE_LE_SC_RANDOM, &values.randomizer);
+                }
+
+                eir
+            }
+            _ => {
+                if let Some(values) = self.p192 {
+                    push_eir(&mut eir, EIR_TYPE_SSP_HASH_C192, &values.hash);
+                    push_eir(&mut eir, EIR_TYPE_SSP_RAND_R192, &values.randomizer);
+                }
+
+                if let Some(values) = self.p256 {
+                    push_eir(&mut eir, EIR_TYPE_SSP_HASH_C256, &values.hash);
+                    push_eir(&mut eir, EIR_TYPE_SSP_RAND_R256, &values.randomizer);
+                }
+
+                let length = 8 + eir.len();
+                let mut blob = vec![(length & 0xff) as u8, (length >> 8 & 0xff) as u8];
+                blob.extend_from_slice(&address.address);
+                blob.append(&mut eir);
+
+                blob
+            }
+        }
+    }
+}
+
+fn push_eir(eir: &mut Vec<u8>, eir_type: u8, value: &[u8]) {
+    eir.push(value.len() as u8 + 1);
+    eir.push(eir_type);
+    eir.extend_from_slice(value);
+}
+
+/// Iterates over `(type, value)` of EIR/AD structures, stopping at the first zero length
+/// or truncated structure.
+pub struct EirIterator<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> EirIterator<'a> {
+    pub fn new(data: &'a [u8]) -> EirIterator<'a> {
+        EirIterator { data }
+    }
+}
+
+impl<'a> Iterator for EirIterator<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let length = *self.data.first()? as usize;
+        if length == 0 || self.data.len() < 1 + length {
+            return None;
+        }
+
+        let item = (self.data[1], &self.data[2..1 + length]);
+        self.data = &self.data[1 + length..];
+
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn le_eir_round_trip() {
+        let a = Address::from_string("C1:BC:CD:DE:EF:F1", AddressType::LeRandom).unwrap();
+        let data = OobData {
+            p192: None,
+            p256: Some(OobValues {
+                hash: [0x11; 16],
+                randomizer: [0x22; 16],
+            }),
+        };
+
+        let eir = data.to_eir(&a, LeRole::PeripheralPreferred);
+        assert_eq!(&[8, EIR_TYPE_LE_BDADDR, 0xf1, 0xef], &eir[0..4]);
+        assert_eq!(1, eir[8]);
+        assert_eq!(&[2, EIR_TYPE_LE_ROLE, 0x02], &eir[9..12]);
+        assert_eq!(data, OobData::from_eir(&eir));
+        assert_eq!(Some(LeRole::PeripheralPreferred), LeRole::from_eir(&eir));
+
+        // BR/EDR blobs carry no role
+        let a = Address::from_string("C1:BC:CD:DE:EF:F1", AddressType::BrEdr).unwrap();
+        let blob = data.to_eir(&a, LeRole::PeripheralPreferred);
+        assert_eq!(None, LeRole::from_eir(&blob[8..]));
+    }
+
+    #[test]
+    fn short_form() {
+        let data = OobData {
+            p192: Some(OobValues {
+                hash: [0x11; 16],
+                randomizer: [0x22; 16],
+            }),
+            p256: None,
+        };
+
+        assert_eq!(32, data.to_bytes().len());
+        assert_eq!(data, OobData::from_bytes(&data.to_bytes()));
+    }
+}
@@ -0,0 +1,31 @@
+use error::Error;
+use std::time;
+
+command! {
+    /// Command with a caller supplied opcode and parameters, used by `BTMgmt::send_raw`.
+    pub struct RawCommand(opcode) -> Vec<u8> {
+        |params| Ok(params.rest().to_vec())
+    }
+}
+
+impl RawCommand {
+    pub fn new(ctrl_index: u16, opcode: u16, params: &[u8], timeout: time::Duration) -> RawCommand {
+        RawCommand::request(ctrl_index, opcode, params.to_vec(), timeout)
+    }
+}
+
+/// Status and return parameters of a command sent with `BTMgmt::send_raw`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawResponse {
+    pub opcode: u16,
+    pub ctrl_index: u16,
+    pub status: u8,
+    pub params: Vec<u8>,
+}
+
+impl RawResponse {
+    /// Returns the error matching a non-zero status.
+    pub fn error(&self) -> Option<Error> {
+        Error::from_status(self.opcode, self.ctrl_index, self.status)
+    }
+}
@@ -0,0 +1,17 @@
+use adv_monitor::AdvMonitorFeatures;
+
+use std::time;
+
+pub const READ_ADV_MONITOR_FEATURES_OPCODE: u16 = 0x0051;
+
+command! {
+    pub struct ReadAdvMonitorFeaturesCommand(READ_ADV_MONITOR_FEATURES_OPCODE) -> AdvMonitorFeatures {
+        |params| params.decode("monitor features", AdvMonitorFeatures::from_bytes)
+    }
+}
+
+impl ReadAdvMonitorFeaturesCommand {
+    pub fn new(ctrl_index: u16, timeout: time::Duration) -> ReadAdvMonitorFeaturesCommand {
+        ReadAdvMonitorFeaturesCommand::request(ctrl_index, Vec::new(), timeout)
+    }
+}
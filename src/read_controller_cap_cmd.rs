@@ -0,0 +1,87 @@
+use oob::EirIterator;
+
+use std::time;
+
+pub const READ_CONTROLLER_CAP_OPCODE: u16 = 0x0048;
+
+// capability types, encoded as EIR structures
+const CAP_SECURITY_FLAGS: u8 = 0x01;
+const CAP_MAX_ENC_KEY_SIZE_BREDR: u8 = 0x02;
+const CAP_MAX_ENC_KEY_SIZE_LE: u8 = 0x03;
+const CAP_LE_TX_POWER: u8 = 0x04;
+
+flags! {
+    pub struct SecurityFlags(u8) {
+        const REMOTE_PUBLIC_KEY_VALIDATION_BREDR = 1 << 0;
+        const REMOTE_PUBLIC_KEY_VALIDATION_LE = 1 << 1;
+        const ENC_KEY_SIZE_ENFORCEMENT_BREDR = 1 << 2;
+        const ENC_KEY_SIZE_ENFORCEMENT_LE = 1 << 3;
+    }
+}
+
+/// Security capabilities of a controller. Each value is `None` if the kernel did not
+/// report it, e.g. the LE values for a BR/EDR only controller.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ControllerCapabilities {
+    pub security_flags: Option<SecurityFlags>,
+    pub max_enc_key_size_bredr: Option<u8>,
+    pub max_enc_key_size_le: Option<u8>,
+    /// Minimum and maximum LE TX power in dBm.
+    pub le_tx_power: Option<(i8, i8)>,
+}
+
+impl ControllerCapabilities {
+    /// Decodes the capabilities data, skipping unknown capability types.
+    pub fn from_bytes(data: &[u8]) -> ControllerCapabilities {
+        let mut caps = ControllerCapabilities::default();
+
+        for (cap_type, value) in EirIterator::new(data) {
+            match (cap_type, value.len()) {
+                (CAP_SECURITY_FLAGS, 1) => caps.security_flags = Some(SecurityFlags(value[0])),
+                (CAP_MAX_ENC_KEY_SIZE_BREDR, 1) => caps.max_enc_key_size_bredr = Some(value[0]),
+                (CAP_MAX_ENC_KEY_SIZE_LE, 1) => caps.max_enc_key_size_le = Some(value[0]),
+                (CAP_LE_TX_POWER, 2) => caps.le_tx_power = Some((value[0] as i8, value[1] as i8)),
+                _ => {}
+            }
+        }
+
+        caps
+    }
+}
+
+command! {
+    pub struct ReadControllerCapCommand(READ_CONTROLLER_CAP_OPCODE) -> ControllerCapabilities {
+        |params| {
+            let length = params.u16()? as usize;
+
+            Ok(ControllerCapabilities::from_bytes(params.bytes(length)?))
+        }
+    }
+}
+
+impl ReadControllerCapCommand {
+    pub fn new(ctrl_index: u16, timeout: time::Duration) -> ReadControllerCapCommand {
+        ReadControllerCapCommand::request(ctrl_index, Vec::new(), timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities() {
+        let data = [
+            0x02, 0x01, 0x0f, 0x02, 0x02, 0x10, 0x02, 0x03, 0x10, 0x03, 0x04, 0xe0, 0x0a, 0x02,
+            0x7f, 0x00,
+        ];
+        let caps = ControllerCapabilities::from_bytes(&data);
+        assert!(caps
+            .security_flags
+            .unwrap()
+            .contains(SecurityFlags::ENC_KEY_SIZE_ENFORCEMENT_LE));
+        assert_eq!(Some(16), caps.max_enc_key_size_bredr);
+        assert_eq!(Some(16), caps.max_enc_key_size_le);
+        assert_eq!(Some((-32, 10)), caps.le_tx_power);
+    }
+}
@@ -0,0 +1,37 @@
+use std::time;
+
+pub const READ_CONTROLLER_CONFIG_OPCODE: u16 = 0x0037;
+
+flags! {
+    pub struct ConfigOptions(u32) {
+        const EXTERNAL_CONFIG = 1 << 0;
+        const PUBLIC_ADDRESS = 1 << 1;
+    }
+}
+
+/// A controller is configured, and moves from the unconfigured to the configured index
+/// list, once `missing_options` is empty.
+#[derive(Debug, Clone)]
+pub struct ControllerConfigInfo {
+    pub manufacturer: u16,
+    pub supported_options: ConfigOptions,
+    pub missing_options: ConfigOptions,
+}
+
+command! {
+    pub struct ReadControllerConfigCommand(READ_CONTROLLER_CONFIG_OPCODE) -> ControllerConfigInfo {
+        |params| {
+            Ok(ControllerConfigInfo {
+                manufacturer: params.u16()?,
+                supported_options: ConfigOptions(params.u32()?),
+                missing_options: ConfigOptions(params.u32()?),
+            })
+        }
+    }
+}
+
+impl ReadControllerConfigCommand {
+    pub fn new(ctrl_index: u16, timeout: time::Duration) -> ReadControllerConfigCommand {
+        ReadControllerConfigCommand::request(ctrl_index, Vec::new(), timeout)
+    }
+}
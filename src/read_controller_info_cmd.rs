@@ -0,0 +1,124 @@
+use address::Address;
+use class_of_device::ClassOfDevice;
+use frame::le16;
+use set_local_name_cmd::LocalName;
+
+use std::time;
+
+pub const READ_CONTROLLER_INFO_OPCODE: u16 = 0x0004;
+
+flags! {
+    /// Controller settings as reported in the supported and current settings bitmasks.
+    pub struct Settings(u32) {
+        const POWERED = 1 << 0;
+        const CONNECTABLE = 1 << 1;
+        const FAST_CONNECTABLE = 1 << 2;
+        const DISCOVERABLE = 1 << 3;
+        const BONDABLE = 1 << 4;
+        const LINK_SECURITY = 1 << 5;
+        const SSP = 1 << 6;
+        const BREDR = 1 << 7;
+        const HS = 1 << 8;
+        const LE = 1 << 9;
+        const ADVERTISING = 1 << 10;
+        const SECURE_CONN = 1 << 11;
+        const DEBUG_KEYS = 1 << 12;
+        const PRIVACY = 1 << 13;
+        const CONFIGURATION = 1 << 14;
+        const STATIC_ADDRESS = 1 << 15;
+        const PHY_CONFIGURATION = 1 << 16;
+        const WIDEBAND_SPEECH = 1 << 17;
+    }
+}
+
+impl Settings {
+    // names as printed by BlueZ's btmgmt
+    const NAMES: [&'static str; 18] = [
+        "powered",
+        "connectable",
+        "fast-connectable",
+        "discoverable",
+        "bondable",
+        "link-security",
+        "ssp",
+        "br/edr",
+        "hs",
+        "le",
+        "advertising",
+        "secure-conn",
+        "debug-keys",
+        "privacy",
+        "configuration",
+        "static-addr",
+        "phy-configuration",
+        "wide-band-speech",
+    ];
+
+    /// Names of the set bits, unknown bits are skipped.
+    pub fn names(self) -> Vec<&'static str> {
+        Settings::NAMES
+            .iter()
+            .enumerate()
+            .filter(|&(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControllerInfo {
+    /// The public address, a BR/EDR address type.
+    pub address: Address,
+    pub bluetooth_version: u8,
+    pub manufacturer: u16,
+    pub supported_settings: Settings,
+    pub current_settings: Settings,
+    pub class_of_device: ClassOfDevice,
+    pub name: LocalName,
+}
+
+impl ControllerInfo {
+    pub fn from_bytes(data: &[u8]) -> Option<ControllerInfo> {
+        if data.len() < 20 {
+            return None;
+        }
+
+        let mut address: [u8; 6] = Default::default();
+        address.copy_from_slice(&data[0..6]);
+
+        Some(ControllerInfo {
+            address: Address::from_bytes(address, 0),
+            bluetooth_version: data[6],
+            manufacturer: le16(&data[7..9]),
+            supported_settings: Settings::from_bytes(&data[9..13])?,
+            current_settings: Settings::from_bytes(&data[13..17])?,
+            class_of_device: ClassOfDevice::from_bytes(&data[17..20])?,
+            name: LocalName::from_bytes(&data[20..])?,
+        })
+    }
+}
+
+command! {
+    pub struct ReadControllerInfoCommand(READ_CONTROLLER_INFO_OPCODE) -> ControllerInfo {
+        |params| params.decode("controller info", ControllerInfo::from_bytes)
+    }
+}
+
+impl ReadControllerInfoCommand {
+    pub fn new(ctrl_index: u16, timeout: time::Duration) -> ReadControllerInfoCommand {
+        ReadControllerInfoCommand::request(ctrl_index, Vec::new(), timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_names() {
+        let settings = Settings::POWERED | Settings::LE | Settings(1 << 30);
+        assert!(settings.contains(Settings::LE));
+        assert_eq!(vec!["powered", "le"], settings.names());
+        assert!(Settings::default().names().is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+use experimental::ExpFeature;
+
+use std::time;
+
+pub const READ_EXP_FEATURES_OPCODE: u16 = 0x0049;
+
+command! {
+    pub struct ReadExpFeaturesCommand(READ_EXP_FEATURES_OPCODE) -> Vec<ExpFeature> {
+        |params| {
+            let count = params.u16()?;
+
+            let mut features = vec![];
+            for _ in 0..count {
+                // 20 bytes always decode
+                if let Some(feature) = ExpFeature::from_bytes(params.bytes(20)?) {
+                    features.push(feature);
+                }
+            }
+
+            Ok(features)
+        }
+    }
+}
+
+impl ReadExpFeaturesCommand {
+    pub fn new(ctrl_index: u16, timeout: time::Duration) -> ReadExpFeaturesCommand {
+        ReadExpFeaturesCommand::request(ctrl_index, Vec::new(), timeout)
+    }
+}
@@ -0,0 +1,17 @@
+use oob::OobData;
+
+use std::time;
+
+pub const READ_LOCAL_OOB_DATA_OPCODE: u16 = 0x0020;
+
+command! {
+    pub struct ReadLocalOobDataCommand(READ_LOCAL_OOB_DATA_OPCODE) -> OobData {
+        |params| Ok(OobData::from_bytes(params.rest()))
+    }
+}
+
+impl ReadLocalOobDataCommand {
+    pub fn new(ctrl_index: u16, timeout: time::Duration) -> ReadLocalOobDataCommand {
+        ReadLocalOobDataCommand::request(ctrl_index, Vec::new(), timeout)
+    }
+}
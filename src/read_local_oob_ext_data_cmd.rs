@@ -0,0 +1,45 @@
+use oob::OobData;
+
+use std::time;
+
+pub const READ_LOCAL_OOB_EXT_DATA_OPCODE: u16 = 0x003B;
+
+#[derive(Debug, Clone)]
+pub struct LocalOobExtData {
+    pub address_types: u8,
+    pub eir: Vec<u8>,
+}
+
+impl LocalOobExtData {
+    pub fn oob_data(&self) -> OobData {
+        OobData::from_eir(&self.eir)
+    }
+}
+
+command! {
+    pub struct ReadLocalOobExtDataCommand(READ_LOCAL_OOB_EXT_DATA_OPCODE) -> LocalOobExtData {
+        |params| {
+            let address_types = params.u8()?;
+            let eir_length = params.u16()? as usize;
+
+            Ok(LocalOobExtData {
+                address_types,
+                eir: params.bytes(eir_length)?.to_vec(),
+            })
+        }
+    }
+}
+
+impl ReadLocalOobExtDataCommand {
+    /// `address_types` is a mask of `OOB_ADDRESS_TYPE_*` values; BR/EDR and LE cannot be
+    /// requested together.
+    pub fn new(
+        ctrl_index: u16,
+        address_types: u8,
+        timeout: time::Duration,
+    ) -> ReadLocalOobExtDataCommand {
+        let params = vec![address_types];
+
+        ReadLocalOobExtDataCommand::request(ctrl_index, params, timeout)
+    }
+}
@@ -0,0 +1,24 @@
+use std::time;
+
+pub const READ_UNCONF_INDEX_LIST_OPCODE: u16 = 0x0036;
+
+command! {
+    pub struct ReadUnconfIndexListCommand(READ_UNCONF_INDEX_LIST_OPCODE) -> Vec<u16> {
+        |params| {
+            let count = params.u16()?;
+
+            let mut indexes = vec![];
+            for _ in 0..count {
+                indexes.push(params.u16()?);
+            }
+
+            Ok(indexes)
+        }
+    }
+}
+
+impl ReadUnconfIndexListCommand {
+    pub fn new(timeout: time::Duration) -> ReadUnconfIndexListCommand {
+        ReadUnconfIndexListCommand::request(0xFFFF, Vec::new(), timeout)
+    }
+}
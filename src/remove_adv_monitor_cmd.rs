@@ -0,0 +1,24 @@
+use std::time;
+
+pub const REMOVE_ADV_MONITOR_OPCODE: u16 = 0x0053;
+
+command! {
+    pub struct RemoveAdvMonitorCommand(REMOVE_ADV_MONITOR_OPCODE) -> u16 {
+        |params| params.u16()
+    }
+}
+
+impl RemoveAdvMonitorCommand {
+    pub fn new(
+        ctrl_index: u16,
+        monitor_handle: u16,
+        timeout: time::Duration,
+    ) -> RemoveAdvMonitorCommand {
+        let params = vec![
+            (monitor_handle & 0xff) as u8,
+            (monitor_handle >> 8 & 0xff) as u8,
+        ];
+
+        RemoveAdvMonitorCommand::request(ctrl_index, params, timeout)
+    }
+}
@@ -0,0 +1,26 @@
+use address::Address;
+
+use std::time;
+
+pub const REMOVE_REMOTE_OOB_DATA_OPCODE: u16 = 0x0022;
+
+command! {
+    pub struct RemoveRemoteOobDataCommand(REMOVE_REMOTE_OOB_DATA_OPCODE, address) -> Address {
+        |params| params.address()
+    }
+}
+
+impl RemoveRemoteOobDataCommand {
+    pub fn new(
+        ctrl_index: u16,
+        address: &Address,
+        timeout: time::Duration,
+    ) -> RemoveRemoteOobDataCommand {
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&address.address);
+        params.push(address.address_type.to_param());
+
+        RemoveRemoteOobDataCommand::request(ctrl_index, address, params, timeout)
+    }
+}
@@ -0,0 +1,23 @@
+use class_of_device::ClassOfDevice;
+use uuid::Uuid;
+
+use std::time;
+
+pub const REMOVE_UUID_OPCODE: u16 = 0x0011;
+
+command! {
+    pub struct RemoveUuidCommand(REMOVE_UUID_OPCODE) -> ClassOfDevice {
+        |params| params.decode("class of device", ClassOfDevice::from_bytes)
+    }
+}
+
+impl RemoveUuidCommand {
+    /// The all-zero UUID removes every UUID.
+    pub fn new(ctrl_index: u16, uuid: &Uuid, timeout: time::Duration) -> RemoveUuidCommand {
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&uuid.to_le_bytes());
+
+        RemoveUuidCommand::request(ctrl_index, params, timeout)
+    }
+}
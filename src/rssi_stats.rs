@@ -0,0 +1,76 @@
+use address::Address;
+
+#[derive(Debug, Clone)]
+pub struct RssiStats {
+    pub address: Address,
+    pub samples: usize,
+    pub missed: usize,
+    pub min: Option<i8>,
+    pub max: Option<i8>,
+    pub mean: Option<f64>,
+    pub ewma: Option<f64>,
+    alpha: f64,
+    sum: i64,
+}
+
+impl RssiStats {
+    /// `alpha` is the weight of the newest sample in the exponentially weighted moving
+    /// average and is clamped to [0, 1].
+    pub fn new(address: &Address, alpha: f64) -> RssiStats {
+        RssiStats {
+            address: address.clone(),
+            samples: 0,
+            missed: 0,
+            min: None,
+            max: None,
+            mean: None,
+            ewma: None,
+            alpha: alpha.clamp(0.0, 1.0),
+            sum: 0,
+        }
+    }
+
+    pub fn add(&mut self, rssi: i8) {
+        self.samples += 1;
+        self.sum += i64::from(rssi);
+
+        self.min = Some(self.min.map_or(rssi, |min| min.min(rssi)));
+        self.max = Some(self.max.map_or(rssi, |max| max.max(rssi)));
+        self.mean = Some(self.sum as f64 / self.samples as f64);
+        self.ewma = Some(match self.ewma {
+            Some(ewma) => self.alpha * f64::from(rssi) + (1.0 - self.alpha) * ewma,
+            None => f64::from(rssi),
+        });
+    }
+
+    /// Records a sampling round in which no RSSI was available, either because the device
+    /// was not connected or because the kernel reported the value as unknown.
+    pub fn add_missed(&mut self) {
+        self.missed += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use address::AddressType;
+
+    #[test]
+    fn stats() {
+        let a = Address::from_string("AB:BC:CD:DE:EF:F1", AddressType::LeRandom).unwrap();
+        let mut stats = RssiStats::new(&a, 0.5);
+        assert_eq!(None, stats.mean);
+
+        stats.add(-60);
+        stats.add(-70);
+        stats.add_missed();
+        stats.add(-50);
+
+        assert_eq!(3, stats.samples);
+        assert_eq!(1, stats.missed);
+        assert_eq!(Some(-70), stats.min);
+        assert_eq!(Some(-50), stats.max);
+        assert_eq!(Some(-60.0), stats.mean);
+        assert_eq!(Some(-57.5), stats.ewma);
+    }
+}
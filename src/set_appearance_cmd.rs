@@ -0,0 +1,17 @@
+use std::time;
+
+pub const SET_APPEARANCE_OPCODE: u16 = 0x0043;
+
+command! {
+    pub struct SetAppearanceCommand(SET_APPEARANCE_OPCODE) -> () {
+        |_| Ok(())
+    }
+}
+
+impl SetAppearanceCommand {
+    pub fn new(ctrl_index: u16, appearance: u16, timeout: time::Duration) -> SetAppearanceCommand {
+        let params = vec![(appearance & 0xff) as u8, (appearance >> 8 & 0xff) as u8];
+
+        SetAppearanceCommand::request(ctrl_index, params, timeout)
+    }
+}
@@ -0,0 +1,42 @@
+use read_controller_info_cmd::Settings;
+
+use std::time;
+
+pub const SET_DEBUG_KEYS_OPCODE: u16 = 0x002E;
+
+/// `Keep` stores debug keys created during pairing, `Use` additionally makes the
+/// controller generate debug keys itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugKeysMode {
+    Discard,
+    Keep,
+    Use,
+}
+
+impl DebugKeysMode {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            DebugKeysMode::Discard => 0x00,
+            DebugKeysMode::Keep => 0x01,
+            DebugKeysMode::Use => 0x02,
+        }
+    }
+}
+
+command! {
+    pub struct SetDebugKeysCommand(SET_DEBUG_KEYS_OPCODE) -> Settings {
+        |params| params.decode("current settings", Settings::from_bytes)
+    }
+}
+
+impl SetDebugKeysCommand {
+    pub fn new(
+        ctrl_index: u16,
+        mode: DebugKeysMode,
+        timeout: time::Duration,
+    ) -> SetDebugKeysCommand {
+        let params = vec![mode.to_byte()];
+
+        SetDebugKeysCommand::request(ctrl_index, params, timeout)
+    }
+}
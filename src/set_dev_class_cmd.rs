@@ -0,0 +1,23 @@
+use class_of_device::ClassOfDevice;
+
+use std::time;
+
+pub const SET_DEV_CLASS_OPCODE: u16 = 0x000E;
+
+command! {
+    pub struct SetDevClassCommand(SET_DEV_CLASS_OPCODE) -> ClassOfDevice {
+        |params| params.decode("class of device", ClassOfDevice::from_bytes)
+    }
+}
+
+impl SetDevClassCommand {
+    pub fn new(
+        ctrl_index: u16,
+        class: &ClassOfDevice,
+        timeout: time::Duration,
+    ) -> SetDevClassCommand {
+        let params = vec![class.major_byte(), class.minor_byte()];
+
+        SetDevClassCommand::request(ctrl_index, params, timeout)
+    }
+}
@@ -0,0 +1,32 @@
+use address::Address;
+use get_device_flags_cmd::DeviceFlags;
+
+use std::time;
+
+pub const SET_DEVICE_FLAGS_OPCODE: u16 = 0x0050;
+
+command! {
+    pub struct SetDeviceFlagsCommand(SET_DEVICE_FLAGS_OPCODE, address) -> Address {
+        |params| params.address()
+    }
+}
+
+impl SetDeviceFlagsCommand {
+    pub fn new(
+        ctrl_index: u16,
+        address: &Address,
+        flags: DeviceFlags,
+        timeout: time::Duration,
+    ) -> SetDeviceFlagsCommand {
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&address.address);
+        params.push(address.address_type.to_param());
+        params.push((flags.0 & 0xff) as u8);
+        params.push((flags.0 >> 8 & 0xff) as u8);
+        params.push((flags.0 >> 16 & 0xff) as u8);
+        params.push((flags.0 >> 24 & 0xff) as u8);
+
+        SetDeviceFlagsCommand::request(ctrl_index, address, params, timeout)
+    }
+}
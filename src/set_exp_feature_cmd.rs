@@ -0,0 +1,28 @@
+use experimental::ExpFeature;
+use uuid::Uuid;
+
+use std::time;
+
+pub const SET_EXP_FEATURE_OPCODE: u16 = 0x004A;
+
+command! {
+    pub struct SetExpFeatureCommand(SET_EXP_FEATURE_OPCODE) -> ExpFeature {
+        |params| params.decode("experimental feature", ExpFeature::from_bytes)
+    }
+}
+
+impl SetExpFeatureCommand {
+    pub fn new(
+        ctrl_index: u16,
+        uuid: &Uuid,
+        enabled: bool,
+        timeout: time::Duration,
+    ) -> SetExpFeatureCommand {
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&uuid.to_le_bytes());
+        params.push(enabled as u8);
+
+        SetExpFeatureCommand::request(ctrl_index, params, timeout)
+    }
+}
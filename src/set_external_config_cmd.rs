@@ -0,0 +1,24 @@
+use read_controller_config_cmd::ConfigOptions;
+
+use std::time;
+
+pub const SET_EXTERNAL_CONFIG_OPCODE: u16 = 0x0038;
+
+command! {
+    pub struct SetExternalConfigCommand(SET_EXTERNAL_CONFIG_OPCODE) -> ConfigOptions {
+        |params| params.decode("missing options", ConfigOptions::from_bytes)
+    }
+}
+
+impl SetExternalConfigCommand {
+    /// Marks the controller as configured by an external entity, or clears that mark.
+    pub fn new(
+        ctrl_index: u16,
+        enabled: bool,
+        timeout: time::Duration,
+    ) -> SetExternalConfigCommand {
+        let params = vec![enabled as u8];
+
+        SetExternalConfigCommand::request(ctrl_index, params, timeout)
+    }
+}
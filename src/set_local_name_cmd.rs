@@ -0,0 +1,97 @@
+use error::Error;
+
+use std::time;
+
+pub const SET_LOCAL_NAME_OPCODE: u16 = 0x000F;
+
+// sizes of the name fields including the terminating null byte
+const NAME_SIZE: usize = 249;
+const SHORT_NAME_SIZE: usize = 11;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalName {
+    pub name: String,
+    pub short_name: String,
+}
+
+impl LocalName {
+    pub fn from_bytes(data: &[u8]) -> Option<LocalName> {
+        if data.len() < NAME_SIZE + SHORT_NAME_SIZE {
+            return None;
+        }
+
+        Some(LocalName {
+            name: from_null_terminated(&data[0..NAME_SIZE]),
+            short_name: from_null_terminated(&data[NAME_SIZE..NAME_SIZE + SHORT_NAME_SIZE]),
+        })
+    }
+}
+
+fn from_null_terminated(data: &[u8]) -> String {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[0..end]).into_owned()
+}
+
+command! {
+    pub struct SetLocalNameCommand(SET_LOCAL_NAME_OPCODE) -> LocalName {
+        |params| params.decode("local name", LocalName::from_bytes)
+    }
+}
+
+impl SetLocalNameCommand {
+    /// The name is limited to 248 bytes and the short name to 10 bytes of UTF-8.
+    pub fn new(
+        ctrl_index: u16,
+        name: &str,
+        short_name: &str,
+        timeout: time::Duration,
+    ) -> Result<SetLocalNameCommand, Error> {
+        if name.len() >= NAME_SIZE || short_name.len() >= SHORT_NAME_SIZE {
+            return Err(Error::InvalidParameters);
+        }
+
+        if name.contains('\0') || short_name.contains('\0') {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut params = Vec::new();
+
+        params.extend_from_slice(name.as_bytes());
+        params.resize(NAME_SIZE, 0);
+        params.extend_from_slice(short_name.as_bytes());
+        params.resize(NAME_SIZE + SHORT_NAME_SIZE, 0);
+
+        Ok(SetLocalNameCommand::request(ctrl_index, params, timeout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_lengths() {
+        let timeout = time::Duration::from_secs(1);
+        let name = |length| "a".repeat(length);
+
+        assert!(SetLocalNameCommand::new(0, &name(248), &name(10), timeout).is_ok());
+        assert!(SetLocalNameCommand::new(0, "", "", timeout).is_ok());
+        assert!(SetLocalNameCommand::new(0, &name(249), "", timeout).is_err());
+        assert!(SetLocalNameCommand::new(0, "", &name(11), timeout).is_err());
+        // the limits are in bytes, not characters
+        assert!(SetLocalNameCommand::new(0, &"ä".repeat(125), "", timeout).is_err());
+        assert!(SetLocalNameCommand::new(0, "a\0b", "", timeout).is_err());
+    }
+
+    #[test]
+    fn from_bytes() {
+        let mut data = vec![0u8; NAME_SIZE + SHORT_NAME_SIZE];
+        data[0..4].copy_from_slice(b"hci0");
+        data[NAME_SIZE..NAME_SIZE + 2].copy_from_slice(b"h0");
+
+        let name = LocalName::from_bytes(&data).unwrap();
+        assert_eq!("hci0", name.name);
+        assert_eq!("h0", name.short_name);
+        assert!(LocalName::from_bytes(&data[1..]).is_none());
+    }
+}
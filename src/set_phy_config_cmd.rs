@@ -0,0 +1,24 @@
+use get_phy_config_cmd::Phys;
+
+use std::time;
+
+pub const SET_PHY_CONFIG_OPCODE: u16 = 0x0045;
+
+command! {
+    pub struct SetPhyConfigCommand(SET_PHY_CONFIG_OPCODE) -> () {
+        |_| Ok(())
+    }
+}
+
+impl SetPhyConfigCommand {
+    pub fn new(ctrl_index: u16, selected: Phys, timeout: time::Duration) -> SetPhyConfigCommand {
+        let params = vec![
+            (selected.0 & 0xff) as u8,
+            (selected.0 >> 8 & 0xff) as u8,
+            (selected.0 >> 16 & 0xff) as u8,
+            (selected.0 >> 24 & 0xff) as u8,
+        ];
+
+        SetPhyConfigCommand::request(ctrl_index, params, timeout)
+    }
+}
@@ -0,0 +1,46 @@
+use irk::Irk;
+use read_controller_info_cmd::Settings;
+
+use std::time;
+
+pub const SET_PRIVACY_OPCODE: u16 = 0x002F;
+
+/// With `Limited` the controller uses its identity address while discoverable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivacyMode {
+    Off,
+    On,
+    Limited,
+}
+
+impl PrivacyMode {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            PrivacyMode::Off => 0x00,
+            PrivacyMode::On => 0x01,
+            PrivacyMode::Limited => 0x02,
+        }
+    }
+}
+
+command! {
+    pub struct SetPrivacyCommand(SET_PRIVACY_OPCODE) -> Settings {
+        |params| params.decode("current settings", Settings::from_bytes)
+    }
+}
+
+impl SetPrivacyCommand {
+    pub fn new(
+        ctrl_index: u16,
+        mode: PrivacyMode,
+        irk: &Irk,
+        timeout: time::Duration,
+    ) -> SetPrivacyCommand {
+        let mut params = Vec::new();
+
+        params.push(mode.to_byte());
+        params.extend_from_slice(&irk.to_le_bytes());
+
+        SetPrivacyCommand::request(ctrl_index, params, timeout)
+    }
+}
@@ -0,0 +1,27 @@
+use address::Address;
+use read_controller_config_cmd::ConfigOptions;
+
+use std::time;
+
+pub const SET_PUBLIC_ADDRESS_OPCODE: u16 = 0x0039;
+
+command! {
+    pub struct SetPublicAddressCommand(SET_PUBLIC_ADDRESS_OPCODE) -> ConfigOptions {
+        |params| params.decode("missing options", ConfigOptions::from_bytes)
+    }
+}
+
+impl SetPublicAddressCommand {
+    /// The address type is ignored, the kernel rejects the all-zero address.
+    pub fn new(
+        ctrl_index: u16,
+        address: &Address,
+        timeout: time::Duration,
+    ) -> SetPublicAddressCommand {
+        let mut params = Vec::new();
+
+        params.extend_from_slice(&address.address);
+
+        SetPublicAddressCommand::request(ctrl_index, params, timeout)
+    }
+}
@@ -0,0 +1,41 @@
+use read_controller_info_cmd::Settings;
+
+use std::time;
+
+pub const SET_SECURE_CONN_OPCODE: u16 = 0x002D;
+
+/// `Only` rejects pairing with devices which do not support Secure Connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecureConnectionsMode {
+    Off,
+    On,
+    Only,
+}
+
+impl SecureConnectionsMode {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            SecureConnectionsMode::Off => 0x00,
+            SecureConnectionsMode::On => 0x01,
+            SecureConnectionsMode::Only => 0x02,
+        }
+    }
+}
+
+command! {
+    pub struct SetSecureConnCommand(SET_SECURE_CONN_OPCODE) -> Settings {
+        |params| params.decode("current settings", Settings::from_bytes)
+    }
+}
+
+impl SetSecureConnCommand {
+    pub fn new(
+        ctrl_index: u16,
+        mode: SecureConnectionsMode,
+        timeout: time::Duration,
+    ) -> SetSecureConnCommand {
+        let params = vec![mode.to_byte()];
+
+        SetSecureConnCommand::request(ctrl_index, params, timeout)
+    }
+}
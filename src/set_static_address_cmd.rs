@@ -0,0 +1,70 @@
+use address::Address;
+use error::Error;
+use read_controller_info_cmd::Settings;
+
+use std::time;
+
+pub const SET_STATIC_ADDRESS_OPCODE: u16 = 0x002B;
+
+command! {
+    pub struct SetStaticAddressCommand(SET_STATIC_ADDRESS_OPCODE) -> Settings {
+        |params| params.decode("current settings", Settings::from_bytes)
+    }
+}
+
+impl SetStaticAddressCommand {
+    /// A static random address has the two most significant bits set and is neither all
+    /// ones nor all zeros in the remaining bits. The all-zero address is accepted as well and
+    /// clears a previously configured static address.
+    pub fn new(
+        ctrl_index: u16,
+        address: &Address,
+        timeout: time::Duration,
+    ) -> Result<SetStaticAddressCommand, Error> {
+        let a = &address.address;
+        if *a != [0; 6] {
+            if a[5] & 0xc0 != 0xc0 {
+                return Err(Error::InvalidParameters);
+            }
+
+            if *a == [0xff; 6] || (a[0..5] == [0; 5] && a[5] == 0xc0) {
+                return Err(Error::InvalidParameters);
+            }
+        }
+
+        let mut params = Vec::new();
+
+        params.extend_from_slice(a);
+
+        Ok(SetStaticAddressCommand::request(
+            ctrl_index, params, timeout,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use address::AddressType;
+
+    #[test]
+    fn validation() {
+        let timeout = time::Duration::from_secs(1);
+        let valid = |address: &str| {
+            let a = Address::from_string(address, AddressType::LeRandom).unwrap();
+            SetStaticAddressCommand::new(0, &a, timeout).is_ok()
+        };
+
+        assert!(valid("C1:BC:CD:DE:EF:F1"));
+        assert!(valid("C0:00:00:00:00:01"));
+        assert!(valid("FF:FF:FF:FF:FF:FE"));
+        // clears the static address
+        assert!(valid("00:00:00:00:00:00"));
+        // the two most significant bits are not both set
+        assert!(!valid("81:BC:CD:DE:EF:F1"));
+        assert!(!valid("41:BC:CD:DE:EF:F1"));
+        // the remaining bits are all zeros or all ones
+        assert!(!valid("C0:00:00:00:00:00"));
+        assert!(!valid("FF:FF:FF:FF:FF:FF"));
+    }
+}
@@ -0,0 +1,601 @@
+use error::Error;
+use transport::Transport;
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time;
+
+use {COMMAND_RESPONSE_EVENT, COMMAND_STATUS_EVENT};
+
+type Handler = Box<dyn FnMut(u16, u16, &[u8]) -> Vec<Vec<u8>> + Send>;
+
+struct Kernel {
+    handler: Handler,
+    queues: Vec<VecDeque<Vec<u8>>>,
+    written: Vec<Vec<u8>>,
+}
+
+/// An in-memory stand-in for the kernel's mgmt interface. Every command written is passed
+/// to the handler as `(opcode, ctrl_index, params)` and the frames it returns are delivered:
+/// command complete and command status frames to the channel which sent the command, all
+/// other events to every channel opened through `open_observer`.
+#[derive(Clone)]
+pub struct SimulatedTransport {
+    kernel: Arc<Mutex<Kernel>>,
+    id: usize,
+}
+
+impl SimulatedTransport {
+    pub fn new<F>(handler: F) -> SimulatedTransport
+    where
+        F: FnMut(u16, u16, &[u8]) -> Vec<Vec<u8>> + Send + 'static,
+    {
+        SimulatedTransport {
+            kernel: Arc::new(Mutex::new(Kernel {
+                handler: Box::new(handler),
+                queues: vec![VecDeque::new()],
+                written: Vec::new(),
+            })),
+            id: 0,
+        }
+    }
+
+    /// Every frame written to any channel of the simulated kernel, in order.
+    pub fn written(&self) -> Vec<Vec<u8>> {
+        match self.kernel.lock() {
+            Ok(kernel) => kernel.written.clone(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Delivers an unsolicited event to every channel.
+    pub fn inject(&self, frame: Vec<u8>) {
+        if let Ok(mut kernel) = self.kernel.lock() {
+            for queue in kernel.queues.iter_mut() {
+                queue.push_back(frame.clone());
+            }
+        }
+    }
+}
+
+impl Transport for SimulatedTransport {
+    fn write(&self, frame: &[u8]) -> Result<(), Error> {
+        if frame.len() < 6 {
+            return Err(Error::InvalidParameters);
+        }
+
+        let opcode = u16::from(frame[0]) | (u16::from(frame[1]) << 8);
+        let ctrl_index = u16::from(frame[2]) | (u16::from(frame[3]) << 8);
+
+        let mut kernel = self.kernel.lock().map_err(|_| Error::UnknownError)?;
+        kernel.written.push(frame.to_vec());
+
+        let replies = (kernel.handler)(opcode, ctrl_index, &frame[6..frame.len()]);
+        for reply in replies {
+            let code = reply.first().cloned();
+            if code == Some(COMMAND_RESPONSE_EVENT) || code == Some(COMMAND_STATUS_EVENT) {
+                kernel.queues[self.id].push_back(reply);
+            } else {
+                for queue in kernel.queues.iter_mut() {
+                    queue.push_back(reply.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read(&self, buffer: &mut [u8], timeout: time::Duration) -> Result<usize, Error> {
+        let start = time::Instant::now();
+        loop {
+            {
+                let mut kernel = self.kernel.lock().map_err(|_| Error::UnknownError)?;
+                if let Some(frame) = kernel.queues[self.id].pop_front() {
+                    let length = frame.len().min(buffer.len());
+                    buffer[0..length].copy_from_slice(&frame[0..length]);
+                    return Ok(length);
+                }
+            }
+
+            if start.elapsed() >= timeout {
+                return Ok(0);
+            }
+
+            thread::sleep(time::Duration::from_millis(1));
+        }
+    }
+
+    fn open_observer(&self) -> Result<Box<dyn Transport>, Error> {
+        let mut kernel = self.kernel.lock().map_err(|_| Error::UnknownError)?;
+        kernel.queues.push(VecDeque::new());
+
+        Ok(Box::new(SimulatedTransport {
+            kernel: self.kernel.clone(),
+            id: kernel.queues.len() - 1,
+        }))
+    }
+}
+
+/// Builds a Command Complete event frame.
+pub fn command_complete(opcode: u16, ctrl_index: u16, status: u8, params: &[u8]) -> Vec<u8> {
+    let mut event_params = vec![(opcode & 0xff) as u8, (opcode >> 8 & 0xff) as u8, status];
+    event_params.extend_from_slice(params);
+
+    event(u16::from(COMMAND_RESPONSE_EVENT), ctrl_index, &event_params)
+}
+
+/// Builds a Command Status event frame.
+pub fn command_status(opcode: u16, ctrl_index: u16, status: u8) -> Vec<u8> {
+    let event_params = [(opcode & 0xff) as u8, (opcode >> 8 & 0xff) as u8, status];
+
+    event(u16::from(COMMAND_STATUS_EVENT), ctrl_index, &event_params)
+}
+
+pub fn event(event_code: u16, ctrl_index: u16, params: &[u8]) -> Vec<u8> {
+    let mut v = vec![
+        (event_code & 0xff) as u8,
+        (event_code >> 8 & 0xff) as u8,
+        (ctrl_index & 0xff) as u8,
+        (ctrl_index >> 8 & 0xff) as u8,
+        (params.len() & 0xff) as u8,
+        (params.len() >> 8 & 0xff) as u8,
+    ];
+    v.extend_from_slice(params);
+
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use address::{Address, AddressType};
+    use btsnoop;
+    use experimental;
+    use irk::Irk;
+    use oob::{OobData, OobValues};
+    use {
+        AddDeviceAction, BTMgmt, Command, ConfigOptions, DebugKeysMode, DisconnectReason, Params,
+        PrivacyMode, SecureConnectionsMode, Settings, Status,
+        BTMGMT_CMD_OPCODE_ADD_REMOTE_OOB_DATA, BTMGMT_CMD_OPCODE_DISCONNECT,
+        BTMGMT_CMD_OPCODE_READ_CONTROLLER_CONFIG, BTMGMT_CMD_OPCODE_SET_DEBUG_KEYS,
+        BTMGMT_CMD_OPCODE_SET_PRIVACY, BTMGMT_CMD_OPCODE_SET_SECURE_CONN,
+        BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED,
+    };
+
+    #[test]
+    fn add_remote_oob_data() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, params| {
+            vec![command_complete(opcode, ctrl_index, 0, &params[0..7])]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport.clone()));
+
+        let a = Address::from_string("C1:BC:CD:DE:EF:F1", AddressType::LeRandom).unwrap();
+        let data = OobData {
+            p192: None,
+            p256: Some(OobValues {
+                hash: [0x11; 16],
+                randomizer: [0x22; 16],
+            }),
+        };
+
+        assert_eq!(a, btmgmt.add_remote_oob_data(0, &a, &data).unwrap());
+
+        let written = transport.written();
+        assert_eq!(1, written.len());
+        assert_eq!(6 + 7 + 64, written[0].len());
+        assert_eq!(
+            BTMGMT_CMD_OPCODE_ADD_REMOTE_OOB_DATA,
+            u16::from(written[0][0]) | (u16::from(written[0][1]) << 8)
+        );
+        assert_eq!(&[0u8; 32][..], &written[0][13..45]);
+        assert_eq!(&[0x11u8; 16][..], &written[0][45..61]);
+    }
+
+    struct ReadVersion;
+
+    impl Command for ReadVersion {
+        type Response = (u8, u16);
+
+        fn opcode(&self) -> u16 {
+            0x0001
+        }
+        fn ctrl_index(&self) -> u16 {
+            0xffff
+        }
+        fn encode(&self, _buffer: &mut Vec<u8>) {}
+        fn decode(&self, params: &mut Params) -> Result<(u8, u16), Error> {
+            Ok((params.u8()?, params.u16()?))
+        }
+    }
+
+    #[test]
+    fn execute() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, _| {
+            vec![
+                // a response for another controller is skipped
+                command_complete(opcode, 0, 0, &[0x00, 0x00, 0x00]),
+                command_complete(opcode, ctrl_index, 0, &[0x01, 0x16, 0x00]),
+            ]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+
+        assert_eq!((1, 22), btmgmt.execute(ReadVersion).unwrap());
+    }
+
+    #[test]
+    fn matches_address() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, params| {
+            let mut other = params[0..7].to_vec();
+            other[0] ^= 0xff;
+            vec![
+                // a response for another device is skipped
+                command_complete(opcode, ctrl_index, 0, &other),
+                command_complete(opcode, ctrl_index, 0, &params[0..7]),
+            ]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport.clone()));
+
+        let a = Address::from_string("C1:BC:CD:DE:EF:F1", AddressType::Unknown).unwrap();
+        let disconnected = btmgmt.disconnect(0, &a).unwrap();
+        assert_eq!(a.address, disconnected.address);
+        // without an encoding of its own the type is sent as BR/EDR
+        assert_eq!(AddressType::BrEdr, disconnected.address_type);
+        assert_eq!(0, transport.written()[0][12]);
+    }
+
+    #[test]
+    fn disconnect_and_wait() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, params| {
+            assert_eq!(BTMGMT_CMD_OPCODE_DISCONNECT, opcode);
+            let mut other = params[0..7].to_vec();
+            other[0] ^= 0xff;
+            other.push(0x01);
+            let mut elsewhere = params[0..7].to_vec();
+            elsewhere.push(0x02);
+            let mut disconnected = params[0..7].to_vec();
+            disconnected.push(0x05);
+            vec![
+                command_complete(opcode, ctrl_index, 0, &params[0..7]),
+                // events for another controller or device are skipped
+                event(BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED, 1, &elsewhere),
+                event(BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED, ctrl_index, &other),
+                event(
+                    BTMGMT_EVENT_CODE_DEVICE_DISCONNECTED,
+                    ctrl_index,
+                    &disconnected,
+                ),
+            ]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+
+        let a = Address::from_string("C1:BC:CD:DE:EF:F1", AddressType::LeRandom).unwrap();
+        assert_eq!(
+            DisconnectReason::LocalHostSuspend,
+            btmgmt
+                .disconnect_and_wait(0, &a, time::Duration::from_secs(1))
+                .unwrap()
+        );
+
+        // the command completes but the device never reports the disconnection
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, params| {
+            vec![command_complete(opcode, ctrl_index, 0, &params[0..7])]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+
+        assert!(matches!(
+            btmgmt.disconnect_and_wait(0, &a, time::Duration::from_millis(20)),
+            Err(Error::LocalTimeout)
+        ));
+    }
+
+    #[test]
+    fn current_settings() {
+        // each command turns on the setting it controls
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, _| {
+            let setting = match opcode {
+                BTMGMT_CMD_OPCODE_SET_PRIVACY => Settings::PRIVACY,
+                BTMGMT_CMD_OPCODE_SET_SECURE_CONN => Settings::SECURE_CONN,
+                BTMGMT_CMD_OPCODE_SET_DEBUG_KEYS => Settings::DEBUG_KEYS,
+                _ => Settings::STATIC_ADDRESS,
+            };
+            let settings = Settings::POWERED | Settings::LE | setting;
+            vec![command_complete(
+                opcode,
+                ctrl_index,
+                0,
+                &settings.0.to_le_bytes(),
+            )]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+
+        let a = Address::from_string("C1:BC:CD:DE:EF:F1", AddressType::LeRandom).unwrap();
+        let settings = btmgmt.set_static_address(0, &a).unwrap();
+        assert!(settings.contains(Settings::STATIC_ADDRESS));
+
+        let irk = Irk { key: [0x11; 16] };
+        let settings = btmgmt.set_privacy(0, PrivacyMode::On, &irk).unwrap();
+        assert!(settings.contains(Settings::POWERED | Settings::LE | Settings::PRIVACY));
+        assert!(!settings.contains(Settings::STATIC_ADDRESS));
+
+        let settings = btmgmt
+            .set_secure_connections(0, SecureConnectionsMode::On)
+            .unwrap();
+        assert!(settings.contains(Settings::SECURE_CONN));
+
+        let settings = btmgmt.set_debug_keys(0, DebugKeysMode::Keep).unwrap();
+        assert!(settings.contains(Settings::DEBUG_KEYS));
+    }
+
+    #[test]
+    fn added_devices() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, params| {
+            vec![command_complete(opcode, ctrl_index, 0, &params[0..7])]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport.clone()));
+
+        let a = Address::from_string("C1:BC:CD:DE:EF:F1", AddressType::LeRandom).unwrap();
+        let b = Address::from_string("00:11:22:33:44:55", AddressType::BrEdr).unwrap();
+        let actions = |ctrl_index| {
+            btmgmt
+                .added_devices(ctrl_index)
+                .into_iter()
+                .map(|d| (d.address, d.action))
+                .collect::<Vec<_>>()
+        };
+
+        btmgmt
+            .add_device(0, &a, AddDeviceAction::AutoConnect)
+            .unwrap();
+        btmgmt
+            .add_device(0, &b, AddDeviceAction::AllowIncoming)
+            .unwrap();
+        btmgmt
+            .add_device(1, &a, AddDeviceAction::BackgroundScan)
+            .unwrap();
+        // adding again replaces the action
+        btmgmt
+            .add_device(0, &a, AddDeviceAction::BackgroundScan)
+            .unwrap();
+        assert_eq!(
+            vec![
+                (b.clone(), AddDeviceAction::AllowIncoming),
+                (a.clone(), AddDeviceAction::BackgroundScan),
+            ],
+            actions(0)
+        );
+
+        btmgmt.remove_device(0, &b).unwrap();
+        assert_eq!(
+            vec![(a.clone(), AddDeviceAction::BackgroundScan)],
+            actions(0)
+        );
+
+        // the all-zero address removes every device of that controller only
+        btmgmt
+            .add_device(0, &b, AddDeviceAction::AutoConnect)
+            .unwrap();
+        let all = Address::from_bytes([0; 6], 0);
+        btmgmt.remove_device(0, &all).unwrap();
+        assert!(actions(0).is_empty());
+        assert_eq!(
+            vec![(a.clone(), AddDeviceAction::BackgroundScan)],
+            actions(1)
+        );
+
+        // actions only known from events are not sent
+        let written = transport.written().len();
+        assert!(matches!(
+            btmgmt.add_device(0, &a, AddDeviceAction::Other(0x07)),
+            Err(Error::InvalidParameters)
+        ));
+        assert_eq!(written, transport.written().len());
+    }
+
+    #[test]
+    fn controller_config() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, _| {
+            let params: &[u8] = if opcode == BTMGMT_CMD_OPCODE_READ_CONTROLLER_CONFIG {
+                // manufacturer, supported and missing options
+                &[0x0f, 0x00, 0x03, 0x00, 0x00, 0x80, 0x02, 0x00, 0x00, 0x00]
+            } else {
+                &[0x00, 0x00, 0x00, 0x00]
+            };
+            vec![command_complete(opcode, ctrl_index, 0, params)]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+
+        let info = btmgmt.read_controller_config(0).unwrap();
+        assert_eq!(0x000f, info.manufacturer);
+        assert!(info
+            .supported_options
+            .contains(ConfigOptions::EXTERNAL_CONFIG | ConfigOptions::PUBLIC_ADDRESS));
+        // unknown bits are kept
+        assert_eq!(0x8000_0003, info.supported_options.0);
+        assert_eq!(ConfigOptions::PUBLIC_ADDRESS, info.missing_options);
+        assert!(!info
+            .missing_options
+            .contains(ConfigOptions::EXTERNAL_CONFIG));
+
+        let a = Address::from_string("00:11:22:33:44:55", AddressType::BrEdr).unwrap();
+        assert!(btmgmt.set_public_address(0, &a).unwrap().is_empty());
+    }
+
+    #[test]
+    fn default_config() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, params| {
+            let reply = if params.is_empty() {
+                vec![0x1d, 0x00, 0x02, 0x80, 0x00]
+            } else {
+                Vec::new()
+            };
+            vec![command_complete(opcode, ctrl_index, 0, &reply)]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport.clone()));
+
+        let config = btmgmt.read_default_system_config(0).unwrap();
+        assert_eq!(Some(0x0080), config.get_u16(0x001d));
+        btmgmt.read_default_runtime_config(0).unwrap();
+        btmgmt.set_default_system_config(0, &config).unwrap();
+        btmgmt.set_default_runtime_config(0, &config).unwrap();
+
+        let opcodes = transport
+            .written()
+            .iter()
+            .map(|frame| u16::from(frame[0]) | (u16::from(frame[1]) << 8))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0x004b, 0x004d, 0x004c, 0x004e], opcodes);
+        assert_eq!(
+            &[0x1d, 0x00, 0x02, 0x80, 0x00][..],
+            &transport.written()[2][6..]
+        );
+    }
+
+    #[test]
+    fn read_exp_features() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, _| {
+            let mut params = vec![0x03, 0x00];
+            for &(uuid, flags) in &[
+                (experimental::DEBUG, 0x00),
+                (experimental::LL_PRIVACY, 0x01),
+                (experimental::QUALITY_REPORT, 0x03),
+            ] {
+                params.extend_from_slice(&uuid.to_le_bytes());
+                params.extend_from_slice(&[flags, 0x00, 0x00, 0x00]);
+            }
+            vec![command_complete(opcode, ctrl_index, 0, &params)]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+
+        let features = btmgmt.read_exp_features(0xffff).unwrap();
+        assert_eq!(3, features.len());
+        assert_eq!(experimental::DEBUG, features[0].uuid);
+        assert!(!features[0].is_enabled());
+        assert_eq!(experimental::LL_PRIVACY, features[1].uuid);
+        assert!(features[1].is_enabled());
+        assert_eq!(experimental::QUALITY_REPORT, features[2].uuid);
+        assert_eq!(
+            experimental::EXP_FEATURE_ENABLED | experimental::EXP_FEATURE_SETTINGS_CHANGED,
+            features[2].flags
+        );
+    }
+
+    #[test]
+    fn truncated_response() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, params| {
+            vec![command_complete(opcode, ctrl_index, 0, &params[0..3])]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+
+        let a = Address::from_string("C1:BC:CD:DE:EF:F1", AddressType::LeRandom).unwrap();
+        assert!(matches!(
+            btmgmt.get_clock_info(0, &a),
+            Err(Error::MalformedResponse(_))
+        ));
+    }
+
+    #[test]
+    fn trace() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, _| {
+            vec![command_complete(opcode, ctrl_index, 0, &[0x01, 0x16, 0x00])]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+        let path =
+            std::env::temp_dir().join(format!("btmgmt-trace-{}.btsnoop", std::process::id()));
+        let tracer = btsnoop::Tracer::create(&path).unwrap();
+        btmgmt.set_tracer(Some(tracer.clone()));
+
+        btmgmt.execute(ReadVersion).unwrap();
+        tracer.set_enabled(false);
+        btmgmt.execute(ReadVersion).unwrap();
+        tracer.flush().unwrap();
+
+        let records = btsnoop::read_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(btsnoop::Direction::Sent, records[0].direction);
+        assert_eq!(vec![0x01, 0x00, 0xff, 0xff, 0x00, 0x00], records[0].frame);
+        assert_eq!(btsnoop::Direction::Received, records[1].direction);
+        assert_eq!(
+            command_complete(0x0001, 0xffff, 0, &[0x01, 0x16, 0x00]),
+            records[1].frame
+        );
+    }
+
+    #[test]
+    fn send_raw() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, params| {
+            let status = if params.is_empty() { 0x0d } else { 0 };
+            vec![command_complete(opcode, ctrl_index, status, params)]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+        let timeout = time::Duration::from_secs(1);
+
+        let response = btmgmt.send_raw(0x0057, 1, &[0x01, 0x02], timeout).unwrap();
+        assert_eq!(0, response.status);
+        assert_eq!(vec![0x01, 0x02], response.params);
+        assert!(response.error().is_none());
+
+        let response = btmgmt.send_raw(0x0057, 1, &[], timeout).unwrap();
+        assert_eq!(0x0d, response.status);
+        assert!(response.params.is_empty());
+        assert!(matches!(
+            response.error(),
+            Some(Error::Command {
+                opcode: 0x0057,
+                ctrl_index: 1,
+                status: Status::InvalidParameters,
+            })
+        ));
+    }
+
+    #[test]
+    fn read_controller_info() {
+        let transport = SimulatedTransport::new(|opcode, ctrl_index, _| {
+            let mut info = vec![0xf1, 0xef, 0xde, 0xcd, 0xbc, 0xc1, 0x09, 0x02, 0x00];
+            info.extend_from_slice(&[0xff, 0xff, 0x02, 0x00, 0x81, 0x02, 0x00, 0x00]);
+            info.extend_from_slice(&[0x0c, 0x01, 0x00]);
+            // 249 byte name and 11 byte short name, NUL padded
+            let mut names = [0u8; 260];
+            names[0..4].copy_from_slice(b"hci0");
+            info.extend_from_slice(&names);
+            vec![command_complete(opcode, ctrl_index, 0, &info)]
+        });
+        let btmgmt = BTMgmt::with_transport(Box::new(transport));
+
+        let info = btmgmt.read_controller_info(0).unwrap();
+        assert_eq!("C1:BC:CD:DE:EF:F1", info.address.to_string());
+        assert_eq!(0x09, info.bluetooth_version);
+        assert_eq!(0x0002, info.manufacturer);
+        assert!(info.supported_settings.contains(Settings::WIDEBAND_SPEECH));
+        assert_eq!(
+            Settings::POWERED | Settings::BREDR | Settings::LE,
+            info.current_settings
+        );
+        assert_eq!("hci0", info.name.name);
+    }
+
+    #[test]
+    fn timeout_and_events() {
+        let transport = SimulatedTransport::new(|_, _, _| Vec::new());
+        let mut btmgmt = BTMgmt::with_transport(Box::new(transport.clone()));
+        btmgmt.set_timeout(time::Duration::from_millis(20));
+
+        let start = time::Instant::now();
+        assert!(matches!(
+            btmgmt.read_controller_info(0),
+            Err(Error::LocalTimeout)
+        ));
+        assert!(start.elapsed() < time::Duration::from_millis(500));
+
+        // command responses are left to the command waiting for them
+        transport.inject(command_complete(0x0004, 0, 0, &[]));
+        transport.inject(event(0x0007, 0, &[0x0c, 0x01, 0x00]));
+        let packet = btmgmt.read_event(time::Duration::from_millis(20)).unwrap();
+        assert_eq!(0x0007, packet.unwrap().event_code);
+        assert!(btmgmt
+            .read_event(time::Duration::from_millis(1))
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,117 @@
+//! Raw HCI socket plumbing shared by the mgmt control socket, the event listener, the
+//! monitor reader and the HCI user channel.
+
+use error::Error;
+
+use std::time;
+
+pub const BTPROTO_HCI: i32 = 1;
+pub const HCI_DEV_NONE: u16 = 0xffff;
+pub const HCI_CHANNEL_USER: u16 = 1;
+pub const HCI_CHANNEL_MONITOR: u16 = 2;
+pub const HCI_CHANNEL_CONTROL: u16 = 3;
+
+#[repr(C)]
+pub struct SockAddrHci {
+    pub hci_family: libc::sa_family_t,
+    pub hci_dev: u16,
+    pub hci_channel: u16,
+}
+
+/// Opens a non-blocking raw HCI socket bound to `channel` of controller `dev`. The caller
+/// owns the returned file descriptor.
+pub fn open(dev: u16, channel: u16) -> Result<i32, Error> {
+    let fd = unsafe {
+        libc::socket(
+            libc::PF_BLUETOOTH,
+            libc::SOCK_RAW | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+            BTPROTO_HCI,
+        )
+    };
+
+    if fd < 0 {
+        return Err(Error::last_os_error(Error::Socket));
+    }
+
+    let addr = SockAddrHci {
+        hci_family: libc::AF_BLUETOOTH as u16,
+        hci_dev: dev,
+        hci_channel: channel,
+    };
+
+    let ret_val = unsafe {
+        libc::bind(
+            fd,
+            &addr as *const SockAddrHci as *const libc::sockaddr,
+            std::mem::size_of::<SockAddrHci>() as u32,
+        )
+    };
+
+    if ret_val < 0 {
+        let err = Error::last_os_error(Error::Bind);
+        close(fd);
+        return Err(err);
+    }
+
+    Ok(fd)
+}
+
+pub fn write(fd: i32, data: &[u8]) -> Result<(), Error> {
+    let written = unsafe { libc::write(fd, data.as_ptr() as *const libc::c_void, data.len()) };
+
+    if written < 0 {
+        return Err(Error::last_os_error(Error::Io));
+    }
+
+    Ok(())
+}
+
+/// Waits up to `timeout` for the next packet and copies it into `buffer`. Returns the
+/// number of bytes read, or 0 when no packet arrived in time.
+pub fn read(fd: i32, buffer: &mut [u8], timeout: time::Duration) -> Result<usize, Error> {
+    let mut fds = [libc::pollfd {
+        fd,
+        events: libc::POLLIN | libc::POLLHUP | libc::POLLERR,
+        revents: 0,
+    }];
+
+    let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+    let r = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::c_ulong, timeout_ms) };
+    if r < 0 {
+        let err = std::io::Error::last_os_error();
+        if err.kind() == std::io::ErrorKind::Interrupted {
+            return Ok(0);
+        }
+
+        return Err(Error::Io(err));
+    }
+
+    if r == 0 || fds[0].revents == 0 {
+        return Ok(0);
+    }
+
+    if fds[0].revents & libc::POLLIN == 0 {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::BrokenPipe,
+            "HCI socket hung up",
+        )));
+    }
+
+    let bytes = unsafe { libc::read(fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len()) };
+
+    if bytes < 0 {
+        return Err(Error::last_os_error(Error::Io));
+    }
+
+    if bytes == 0 {
+        return Err(Error::Io(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+
+    Ok(bytes as usize)
+}
+
+pub fn close(fd: i32) {
+    unsafe {
+        libc::close(fd);
+    }
+}
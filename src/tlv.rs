@@ -0,0 +1,236 @@
+use error::Error;
+use frame::le16;
+
+/// Parameter types of the default system configuration. All values are 16 bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemConfigType {
+    BrEdrPageScanType,
+    BrEdrPageScanInterval,
+    BrEdrPageScanWindow,
+    BrEdrInquiryScanType,
+    BrEdrInquiryScanInterval,
+    BrEdrInquiryScanWindow,
+    BrEdrLinkSupervisionTimeout,
+    BrEdrPageTimeout,
+    BrEdrMinSniffInterval,
+    BrEdrMaxSniffInterval,
+    LeAdvMinInterval,
+    LeAdvMaxInterval,
+    LeMultiAdvRotationInterval,
+    LeScanIntervalAutoConnect,
+    LeScanWindowAutoConnect,
+    LeScanIntervalSuspend,
+    LeScanWindowSuspend,
+    LeScanIntervalDiscovery,
+    LeScanWindowDiscovery,
+    LeScanIntervalAdvMonitor,
+    LeScanWindowAdvMonitor,
+    LeScanIntervalConnect,
+    LeScanWindowConnect,
+    LeMinConnInterval,
+    LeMaxConnInterval,
+    LeConnLatency,
+    LeConnSupervisionTimeout,
+    LeAutoConnectTimeout,
+    Unknown(u16),
+}
+
+impl SystemConfigType {
+    pub fn from_u16(value: u16) -> SystemConfigType {
+        match value {
+            0x0000 => SystemConfigType::BrEdrPageScanType,
+            0x0001 => SystemConfigType::BrEdrPageScanInterval,
+            0x0002 => SystemConfigType::BrEdrPageScanWindow,
+            0x0003 => SystemConfigType::BrEdrInquiryScanType,
+            0x0004 => SystemConfigType::BrEdrInquiryScanInterval,
+            0x0005 => SystemConfigType::BrEdrInquiryScanWindow,
+            0x0006 => SystemConfigType::BrEdrLinkSupervisionTimeout,
+            0x0007 => SystemConfigType::BrEdrPageTimeout,
+            0x0008 => SystemConfigType::BrEdrMinSniffInterval,
+            0x0009 => SystemConfigType::BrEdrMaxSniffInterval,
+            0x000a => SystemConfigType::LeAdvMinInterval,
+            0x000b => SystemConfigType::LeAdvMaxInterval,
+            0x000c => SystemConfigType::LeMultiAdvRotationInterval,
+            0x000d => SystemConfigType::LeScanIntervalAutoConnect,
+            0x000e => SystemConfigType::LeScanWindowAutoConnect,
+            0x000f => SystemConfigType::LeScanIntervalSuspend,
+            0x0010 => SystemConfigType::LeScanWindowSuspend,
+            0x0011 => SystemConfigType::LeScanIntervalDiscovery,
+            0x0012 => SystemConfigType::LeScanWindowDiscovery,
+            0x0013 => SystemConfigType::LeScanIntervalAdvMonitor,
+            0x0014 => SystemConfigType::LeScanWindowAdvMonitor,
+            0x0015 => SystemConfigType::LeScanIntervalConnect,
+            0x0016 => SystemConfigType::LeScanWindowConnect,
+            0x0017 => SystemConfigType::LeMinConnInterval,
+            0x0018 => SystemConfigType::LeMaxConnInterval,
+            0x0019 => SystemConfigType::LeConnLatency,
+            0x001a => SystemConfigType::LeConnSupervisionTimeout,
+            0x001b => SystemConfigType::LeAutoConnectTimeout,
+            _ => SystemConfigType::Unknown(value),
+        }
+    }
+
+    pub fn to_u16(self) -> u16 {
+        match self {
+            SystemConfigType::BrEdrPageScanType => 0x0000,
+            SystemConfigType::BrEdrPageScanInterval => 0x0001,
+            SystemConfigType::BrEdrPageScanWindow => 0x0002,
+            SystemConfigType::BrEdrInquiryScanType => 0x0003,
+            SystemConfigType::BrEdrInquiryScanInterval => 0x0004,
+            SystemConfigType::BrEdrInquiryScanWindow => 0x0005,
+            SystemConfigType::BrEdrLinkSupervisionTimeout => 0x0006,
+            SystemConfigType::BrEdrPageTimeout => 0x0007,
+            SystemConfigType::BrEdrMinSniffInterval => 0x0008,
+            SystemConfigType::BrEdrMaxSniffInterval => 0x0009,
+            SystemConfigType::LeAdvMinInterval => 0x000a,
+            SystemConfigType::LeAdvMaxInterval => 0x000b,
+            SystemConfigType::LeMultiAdvRotationInterval => 0x000c,
+            SystemConfigType::LeScanIntervalAutoConnect => 0x000d,
+            SystemConfigType::LeScanWindowAutoConnect => 0x000e,
+            SystemConfigType::LeScanIntervalSuspend => 0x000f,
+            SystemConfigType::LeScanWindowSuspend => 0x0010,
+            SystemConfigType::LeScanIntervalDiscovery => 0x0011,
+            SystemConfigType::LeScanWindowDiscovery => 0x0012,
+            SystemConfigType::LeScanIntervalAdvMonitor => 0x0013,
+            SystemConfigType::LeScanWindowAdvMonitor => 0x0014,
+            SystemConfigType::LeScanIntervalConnect => 0x0015,
+            SystemConfigType::LeScanWindowConnect => 0x0016,
+            SystemConfigType::LeMinConnInterval => 0x0017,
+            SystemConfigType::LeMaxConnInterval => 0x0018,
+            SystemConfigType::LeConnLatency => 0x0019,
+            SystemConfigType::LeConnSupervisionTimeout => 0x001a,
+            SystemConfigType::LeAutoConnectTimeout => 0x001b,
+            SystemConfigType::Unknown(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tlv {
+    pub tlv_type: u16,
+    pub value: Vec<u8>,
+}
+
+/// A list of type-length-value entries, each encoded as a 16-bit type, an 8-bit length and
+/// the value.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TlvList {
+    pub entries: Vec<Tlv>,
+}
+
+impl TlvList {
+    pub fn new() -> TlvList {
+        TlvList {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns `None` if the last entry is truncated.
+    pub fn from_bytes(data: &[u8]) -> Option<TlvList> {
+        let mut entries = Vec::new();
+        let mut rest = data;
+
+        while !rest.is_empty() {
+            if rest.len() < 3 {
+                return None;
+            }
+
+            let tlv_type = le16(rest);
+            let length = rest[2] as usize;
+            if rest.len() < 3 + length {
+                return None;
+            }
+
+            entries.push(Tlv {
+                tlv_type,
+                value: rest[3..3 + length].to_vec(),
+            });
+            rest = &rest[3 + length..];
+        }
+
+        Some(TlvList { entries })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut v = Vec::new();
+        for tlv in &self.entries {
+            if tlv.value.len() > 0xff {
+                return Err(Error::InvalidParameters);
+            }
+
+            v.push((tlv.tlv_type & 0xff) as u8);
+            v.push((tlv.tlv_type >> 8 & 0xff) as u8);
+            v.push(tlv.value.len() as u8);
+            v.extend_from_slice(&tlv.value);
+        }
+
+        if v.len() > 0xffff {
+            return Err(Error::InvalidParameters);
+        }
+
+        Ok(v)
+    }
+
+    pub fn get(&self, tlv_type: u16) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|tlv| tlv.tlv_type == tlv_type)
+            .map(|tlv| &tlv.value[..])
+    }
+
+    /// Replaces the value of an existing entry of the same type or appends a new one.
+    pub fn set(&mut self, tlv_type: u16, value: &[u8]) {
+        match self.entries.iter_mut().find(|tlv| tlv.tlv_type == tlv_type) {
+            Some(tlv) => tlv.value = value.to_vec(),
+            None => self.entries.push(Tlv {
+                tlv_type,
+                value: value.to_vec(),
+            }),
+        }
+    }
+
+    pub fn get_u16(&self, tlv_type: u16) -> Option<u16> {
+        match self.get(tlv_type) {
+            Some(value) if value.len() == 2 => Some(le16(value)),
+            _ => None,
+        }
+    }
+
+    pub fn set_u16(&mut self, tlv_type: u16, value: u16) {
+        self.set(tlv_type, &[(value & 0xff) as u8, (value >> 8 & 0xff) as u8]);
+    }
+
+    pub fn system_value(&self, config_type: SystemConfigType) -> Option<u16> {
+        self.get_u16(config_type.to_u16())
+    }
+
+    pub fn set_system_value(&mut self, config_type: SystemConfigType, value: u16) {
+        self.set_u16(config_type.to_u16(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut list = TlvList::new();
+        list.set_system_value(SystemConfigType::LeMinConnInterval, 0x0018);
+        list.set_system_value(SystemConfigType::LeMaxConnInterval, 0x0028);
+        list.set_system_value(SystemConfigType::LeMinConnInterval, 0x0010);
+
+        let bytes = list.to_bytes().unwrap();
+        assert_eq!(
+            vec![0x17, 0x00, 0x02, 0x10, 0x00, 0x18, 0x00, 0x02, 0x28, 0x00],
+            bytes
+        );
+
+        let parsed = TlvList::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            Some(0x0010),
+            parsed.system_value(SystemConfigType::LeMinConnInterval)
+        );
+        assert!(TlvList::from_bytes(&bytes[0..9]).is_none());
+    }
+}
@@ -0,0 +1,50 @@
+use error::Error;
+use socket::{self, HCI_CHANNEL_CONTROL, HCI_DEV_NONE};
+
+use std::time;
+
+/// The channel `BTMgmt` exchanges mgmt frames over. Besides the control socket this allows
+/// running the library against a simulated kernel, see `simulated::SimulatedTransport`.
+pub trait Transport: Send + Sync {
+    fn write(&self, frame: &[u8]) -> Result<(), Error>;
+
+    /// Waits up to `timeout` for the next frame and copies it into `buffer`. Returns the
+    /// number of bytes read, or 0 when no frame arrived in time.
+    fn read(&self, buffer: &mut [u8], timeout: time::Duration) -> Result<usize, Error>;
+
+    /// Opens another channel to the same mgmt interface, which receives events
+    /// independently of this one.
+    fn open_observer(&self) -> Result<Box<dyn Transport>, Error>;
+}
+
+pub struct SocketTransport {
+    pub fd: i32,
+}
+
+impl SocketTransport {
+    pub fn open() -> Result<SocketTransport, Error> {
+        Ok(SocketTransport {
+            fd: socket::open(HCI_DEV_NONE, HCI_CHANNEL_CONTROL)?,
+        })
+    }
+}
+
+impl Transport for SocketTransport {
+    fn write(&self, frame: &[u8]) -> Result<(), Error> {
+        socket::write(self.fd, frame)
+    }
+
+    fn read(&self, buffer: &mut [u8], timeout: time::Duration) -> Result<usize, Error> {
+        socket::read(self.fd, buffer, timeout)
+    }
+
+    fn open_observer(&self) -> Result<Box<dyn Transport>, Error> {
+        Ok(Box::new(SocketTransport::open()?))
+    }
+}
+
+impl Drop for SocketTransport {
+    fn drop(&mut self) {
+        socket::close(self.fd);
+    }
+}
@@ -0,0 +1,96 @@
+use hex;
+use std::fmt;
+
+// 00000000-0000-1000-8000-00805F9B34FB
+const BLUETOOTH_BASE_UUID: [u8; 16] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5f, 0x9b, 0x34, 0xfb,
+];
+
+/// A 128-bit UUID, stored in big-endian (string) order. The mgmt interface transfers UUIDs
+/// in little-endian order, see `from_le_bytes` and `to_le_bytes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uuid {
+    pub bytes: [u8; 16],
+}
+
+impl Uuid {
+    pub fn from_u16(value: u16) -> Uuid {
+        Uuid::from_u32(u32::from(value))
+    }
+
+    pub fn from_u32(value: u32) -> Uuid {
+        let mut bytes = BLUETOOTH_BASE_UUID;
+        bytes[0] = (value >> 24 & 0xff) as u8;
+        bytes[1] = (value >> 16 & 0xff) as u8;
+        bytes[2] = (value >> 8 & 0xff) as u8;
+        bytes[3] = (value & 0xff) as u8;
+
+        Uuid { bytes }
+    }
+
+    pub fn from_le_bytes(data: &[u8]) -> Option<Uuid> {
+        if data.len() < 16 {
+            return None;
+        }
+
+        let mut bytes: [u8; 16] = Default::default();
+        bytes.copy_from_slice(&data[0..16]);
+        bytes.reverse();
+
+        Some(Uuid { bytes })
+    }
+
+    pub fn to_le_bytes(&self) -> [u8; 16] {
+        let mut bytes = self.bytes;
+        bytes.reverse();
+
+        bytes
+    }
+
+    pub fn from_string(uuid: &str) -> Option<Uuid> {
+        let parts = uuid.split('-').collect::<Vec<&str>>();
+        if parts.len() != 5
+            || parts.iter().map(|p| p.len()).collect::<Vec<usize>>() != [8, 4, 4, 4, 12]
+        {
+            return None;
+        }
+
+        let decoded = match hex::decode(parts.concat()) {
+            Ok(decoded) => decoded,
+            Err(_) => return None,
+        };
+
+        let mut bytes: [u8; 16] = Default::default();
+        bytes.copy_from_slice(&decoded);
+
+        Some(Uuid { bytes })
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}-{}-{}-{}",
+            hex::encode(&self.bytes[0..4]),
+            hex::encode(&self.bytes[4..6]),
+            hex::encode(&self.bytes[6..8]),
+            hex::encode(&self.bytes[8..10]),
+            hex::encode(&self.bytes[10..16])
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_string() {
+        let u = Uuid::from_string("0000110B-0000-1000-8000-00805F9B34FB").unwrap();
+        assert_eq!(Uuid::from_u16(0x110b), u);
+        assert_eq!("0000110b-0000-1000-8000-00805f9b34fb", u.to_string());
+        assert_eq!(0x0b, u.to_le_bytes()[12]);
+        assert!(Uuid::from_string("0000110b-0000-1000-8000-00805f9b34").is_none());
+    }
+}